@@ -0,0 +1,59 @@
+use anyhow::bail;
+use indicatif::MultiProgress;
+use indicatif_log_bridge::LogWrapper;
+use playing_with_sboms::run::run_task;
+use playing_with_sboms::utils::vex::query::{AffectedSboms, FindCve};
+use playing_with_sboms::utils::vex::run_vex;
+use std::collections::BTreeMap;
+
+async fn run(multi: &MultiProgress, cve: &str) -> anyhow::Result<()> {
+    let find = run_vex(multi, FindCve::new(cve)).await?;
+    let advisories = find.advisories.into_inner();
+    let mut products = find.products.into_inner();
+    products.sort_by(|a, b| {
+        (&a.advisory, &a.product.product_id).cmp(&(&b.advisory, &b.product.product_id))
+    });
+
+    println!("{cve}: {} advisories", advisories.len());
+    for advisory in &advisories {
+        println!("  {advisory}");
+    }
+
+    let mut affected = AffectedSboms::new(products);
+    run_task(multi, &mut affected)?;
+
+    let mut by_status = BTreeMap::<_, Vec<_>>::new();
+    for sbom in affected.sboms {
+        by_status.entry(sbom.status).or_default().push(sbom);
+    }
+
+    for (status, sboms) in by_status {
+        println!("{status}:");
+        for sbom in sboms {
+            println!(
+                "  {} ({} / {}): {}",
+                sbom.sbom,
+                sbom.advisory,
+                sbom.product_id,
+                sbom.packages.into_iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+
+    let multi = MultiProgress::new();
+    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
+
+    let Some(cve) = std::env::args().nth(1) else {
+        bail!("Usage: affected <CVE ID>");
+    };
+
+    run(&multi, &cve).await
+}
@@ -0,0 +1,41 @@
+use anyhow::bail;
+use indicatif::MultiProgress;
+use indicatif_log_bridge::LogWrapper;
+use playing_with_sboms::run::run_task;
+use playing_with_sboms::tasks;
+use playing_with_sboms::utils::vex::run_vex;
+use rusqlite::Connection;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+
+    let multi = MultiProgress::new();
+    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
+
+    let mut args = std::env::args().skip(1);
+    let Some(database) = args.next() else {
+        bail!("Usage: export_sqlite <database> [--vex]");
+    };
+    let vex = match args.next().as_deref() {
+        None => false,
+        Some("--vex") => true,
+        Some(other) => bail!("Unknown argument: {other}"),
+    };
+
+    run_task(
+        &multi,
+        &mut tasks::export_sqlite::ExportSbomSqlite::new(Connection::open(&database)?)?,
+    )?;
+
+    if vex {
+        run_vex(
+            &multi,
+            tasks::vex::export_sqlite::ExportCsafSqlite::new(Connection::open(&database)?)?,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
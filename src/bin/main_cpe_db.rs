@@ -0,0 +1,22 @@
+use indicatif::MultiProgress;
+use indicatif_log_bridge::LogWrapper;
+use playing_with_sboms::run::run_task;
+use playing_with_sboms::tasks::main_cpe_db::MainCpeDb;
+use playing_with_sboms::utils::cpe_dictionary::{load_filtered, Filter};
+
+fn main() -> anyhow::Result<()> {
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+
+    let multi = MultiProgress::new();
+    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
+
+    let dictionary = load_filtered(&multi, &Filter::from_env())?;
+    let mut task = MainCpeDb::new(dictionary);
+    if let Ok(lang) = std::env::var("CPE_TITLE_LANG") {
+        task = task.with_lang(lang);
+    }
+    run_task(&multi, &mut task)?;
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anyhow::bail;
+use indicatif::MultiProgress;
+use indicatif_log_bridge::LogWrapper;
+use playing_with_sboms::run::run_task;
+use playing_with_sboms::tasks::query::QuerySboms;
+use playing_with_sboms::tasks::vex::query::QueryCsaf;
+use playing_with_sboms::utils::query_lang::Query;
+use playing_with_sboms::utils::vex::run_vex;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+
+    let multi = MultiProgress::new();
+    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
+
+    let mut args = std::env::args().skip(1);
+    let (Some(kind), Some(query)) = (args.next(), args.next()) else {
+        bail!("Usage: query <sbom|csaf> <query>");
+    };
+    let query = Query::parse(&query)?;
+
+    match kind.as_str() {
+        "sbom" => run_task(&multi, &mut QuerySboms::new(query))?,
+        "csaf" => {
+            run_vex(&multi, QueryCsaf::new(query)).await?;
+        }
+        _ => bail!("Unknown kind of documents: {kind}, expected 'sbom' or 'csaf'"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anyhow::bail;
+use indicatif::MultiProgress;
+use indicatif_log_bridge::LogWrapper;
+use playing_with_sboms::run::run_task;
+use playing_with_sboms::utils::vex::report::{CollectStatements, SbomReport};
+use playing_with_sboms::utils::vex::run_vex;
+
+async fn run(multi: &MultiProgress, name: &str) -> anyhow::Result<()> {
+    let statements = run_vex(multi, CollectStatements::default())
+        .await?
+        .statements
+        .into_inner();
+
+    let mut report = SbomReport::new(name, &statements);
+    run_task(multi, &mut report)?;
+
+    if !report.found {
+        bail!("SBOM not found: {name}");
+    }
+
+    println!("{name}: {} findings", report.findings.len());
+
+    let mut findings = report.findings;
+    // statements are collected concurrently, so sort by all of the key
+    findings.sort_by_key(|finding| {
+        let statement = finding.statement;
+        (
+            &statement.cve,
+            statement.status,
+            &statement.advisory,
+            &statement.product.product_id,
+        )
+    });
+
+    for finding in findings {
+        let statement = finding.statement;
+        println!(
+            "{} [{}] {} / {}",
+            statement.cve, statement.status, statement.advisory, statement.product.product_id
+        );
+
+        println!("  product: {}", statement.product);
+        for (package, main) in &finding.packages {
+            let kind = if *main { "main" } else { "dependency" };
+            println!("  package: {package} ({kind})");
+        }
+        for flag in &statement.flags {
+            println!("  justification: {flag}");
+        }
+        for impact in &statement.impacts {
+            println!("  impact: {impact}");
+        }
+        for remediation in &statement.remediations {
+            println!(
+                "  remediation: {} - {}{}",
+                remediation.category,
+                remediation.details,
+                remediation
+                    .url
+                    .as_ref()
+                    .map(|url| format!(" ({url})"))
+                    .unwrap_or_default()
+            );
+        }
+        if !statement.fixed.is_empty() {
+            println!("  fixed in: {}", statement.fixed.join(", "));
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+
+    let multi = MultiProgress::new();
+    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
+
+    let Some(name) = std::env::args().nth(1) else {
+        bail!("Usage: sbom_report <SBOM document name>");
+    };
+
+    run(&multi, &name).await
+}
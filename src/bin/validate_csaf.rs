@@ -0,0 +1,17 @@
+use indicatif::MultiProgress;
+use indicatif_log_bridge::LogWrapper;
+use playing_with_sboms::tasks::vex::validate_csaf::ValidateCsaf;
+use playing_with_sboms::utils::vex::run_vex;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+
+    let multi = MultiProgress::new();
+    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
+
+    run_vex(&multi, ValidateCsaf::default()).await?;
+
+    Ok(())
+}
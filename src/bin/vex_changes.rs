@@ -0,0 +1,57 @@
+use anyhow::bail;
+use indicatif::MultiProgress;
+use indicatif_log_bridge::LogWrapper;
+use playing_with_sboms::tasks::vex::revisions::{status_changes, Revisions};
+use playing_with_sboms::utils::vex::run_vex_in;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+async fn run(multi: &MultiProgress, before: &Path, after: &Path) -> anyhow::Result<()> {
+    let before = run_vex_in(multi, before, Revisions::default())
+        .await?
+        .advisories
+        .into_inner();
+    let after = run_vex_in(multi, after, Revisions::default())
+        .await?
+        .advisories
+        .into_inner();
+
+    let changes = status_changes(&before, &after);
+
+    let mut transitions = BTreeMap::<_, usize>::new();
+    for change in &changes {
+        println!("{change}");
+        *transitions
+            .entry(format!("{} -> {}", change.before, change.after))
+            .or_default() += 1;
+    }
+
+    let compared = before.keys().filter(|id| after.contains_key(*id)).count();
+    println!(
+        "{} status changes in {compared} advisories, {} removed, {} added",
+        changes.len(),
+        before.len() - compared,
+        after.len() - compared
+    );
+    for (transition, count) in transitions {
+        println!("  {transition}: {count}");
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+
+    let multi = MultiProgress::new();
+    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
+
+    let mut args = std::env::args().skip(1);
+    let (Some(before), Some(after)) = (args.next(), args.next()) else {
+        bail!("Usage: vex_changes <before> <after>");
+    };
+
+    run(&multi, Path::new(&before), Path::new(&after)).await
+}
@@ -0,0 +1,26 @@
+use anyhow::bail;
+use indicatif::MultiProgress;
+use indicatif_log_bridge::LogWrapper;
+use playing_with_sboms::tasks::vex::statistics::{StatementStatistics, Statistics};
+use playing_with_sboms::utils::vex::run_vex;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+
+    let multi = MultiProgress::new();
+    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
+
+    match std::env::args().nth(1).as_deref() {
+        None => {
+            run_vex(&multi, Statistics::default()).await?;
+        }
+        Some("--statements") => {
+            run_vex(&multi, StatementStatistics::default()).await?;
+        }
+        Some(other) => bail!("Usage: vex_stats [--statements], unknown argument: {other}"),
+    }
+
+    Ok(())
+}
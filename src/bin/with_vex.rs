@@ -1,167 +1,108 @@
-use async_trait::async_trait;
-use cpe::{
-    component::Component,
-    cpe::{Cpe, CpeType, Language},
-    uri::Uri,
-};
-use indicatif::MultiProgress;
+use indicatif::{MultiProgress, ProgressBar};
 use indicatif_log_bridge::LogWrapper;
-use log::Level;
-use playing_with_sboms::utils::vex::{ParsedAdvisory, VexTask};
-use playing_with_sboms::{run::run_task, tasks, utils, utils::vex::run_vex};
-use std::collections::BTreeMap;
-use std::mem;
-
-#[derive(Default)]
-pub struct CollectCpe {
-    pub map: BTreeMap<String, usize>,
+use playing_with_sboms::tasks::{all_purl::AllPurl, main_cpe::MainCpe, Task};
+use playing_with_sboms::utils::correlate::{self, CollectProducts, Correlate, Resolution};
+use playing_with_sboms::{run::run_task, utils::vex::run_vex};
+use spdx_rs::models::SPDX;
+use std::collections::BTreeSet;
+
+/// Collect the CPEs and purls of the SBOMs, and correlate them with VEX products, in one pass
+struct SbomPass {
+    cpes: MainCpe,
+    purls: AllPurl,
+    correlate: Correlate,
 }
 
-#[async_trait(?Send)]
-impl VexTask for CollectCpe {
-    async fn process(&mut self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
-        let cpes = utils::vex::product::collect_cpe(&advisory.csaf);
-
-        for cpe in cpes {
-            *self.map.entry(cpe).or_default() += 1;
-        }
-
-        Ok(())
-    }
-}
-
-impl Drop for CollectCpe {
-    fn drop(&mut self) {
-        println!("{} unique entries", self.map.len());
-        for (k, v) in &self.map {
-            println!("{k}: {v}");
-        }
+impl Task for SbomPass {
+    fn process(&mut self, progress: &ProgressBar, spdx: &SPDX) -> anyhow::Result<()> {
+        self.cpes.process(progress, spdx)?;
+        self.purls.process(progress, spdx)?;
+        self.correlate.process(progress, spdx)
     }
 }
 
 async fn run(multi: &MultiProgress) -> anyhow::Result<()> {
-    let mut vex_cpe = CollectCpe::default();
-    let mut sbom_cpe = tasks::main_cpe::MainCpe::default();
-
-    run_vex(multi, &mut vex_cpe).await?;
-    run_task(multi, &mut sbom_cpe)?;
+    let mut vex = run_vex(multi, CollectProducts::default()).await?;
+    let vex_cpe = std::mem::take(vex.cpes.get_mut());
+    let vex_purl = std::mem::take(vex.purls.get_mut());
+
+    let mut sbom = SbomPass {
+        cpes: MainCpe::default(),
+        purls: AllPurl::default(),
+        correlate: Correlate::new(vex.into_products()),
+    };
+    run_task(multi, &mut sbom)?;
+    let SbomPass {
+        cpes: sbom_cpe,
+        purls: sbom_purl,
+        correlate: correlated,
+    } = sbom;
+
+    let (hits, misses) = print_resolutions(correlate::resolve_cpes(
+        vex_cpe.into_keys(),
+        sbom_cpe.map.keys().cloned(),
+    ));
 
-    let vex_cpe = mem::replace(&mut vex_cpe.map, Default::default());
-    let sbom_cpe = mem::replace(&mut sbom_cpe.map, Default::default());
+    println!("Hits: {hits}, Misses: {misses}");
 
-    let (hits, misses) = resolve_cpes(vex_cpe.into_keys(), sbom_cpe.into_keys());
+    let (hits, misses) = print_resolutions(correlate::resolve_purls(
+        vex_purl.into_keys(),
+        sbom_purl.map.keys().cloned(),
+    ));
 
-    println!("Hits: {hits}, Misses: {misses}");
+    println!("Purl hits: {hits}, Misses: {misses}");
 
-    Ok(())
-}
+    let correlations = correlated.into_correlations();
 
-fn fixup_cpe(cpe: String) -> String {
-    // fix `*` to "ANY"
-    let cpe = cpe.replace('*', "");
-    // fix last (language) to "ANY"
-    if cpe.ends_with(':') {
-        let mut c = cpe.chars();
-        c.next_back();
-        c.collect()
-    } else {
-        cpe
+    println!(r#"Advisory,Product,SBOMs,Packages,Evidence"#);
+    let mut hits = 0;
+    for c in &correlations {
+        if !c.matches.is_empty() {
+            hits += 1;
+        }
+        let evidence = c
+            .matches
+            .iter()
+            .flat_map(|m| m.evidence.iter().map(|e| format!("{e:?}")))
+            .collect::<BTreeSet<_>>();
+        println!(
+            r#""{}","{}",{},{},"[{}]""#,
+            c.product.advisory,
+            c.product.product_id,
+            c.sboms().len(),
+            c.matches.len(),
+            evidence.into_iter().collect::<Vec<_>>().join(" ")
+        );
     }
-}
 
-fn resolve_cpes<F, T>(from: F, to: T) -> (usize, usize)
-where
-    F: IntoIterator<Item = String>,
-    T: IntoIterator<Item = String>,
-{
-    let from = from.into_iter().map(fixup_cpe).collect::<Vec<_>>();
-    let to = to.into_iter().map(fixup_cpe).collect::<Vec<_>>();
-
-    let from = from
-        .iter()
-        .map(|cpe| (cpe, Uri::parse(cpe)))
-        .filter_map(|(cpe, r)| match r {
-            Ok(uri) => Some(uri),
-            Err(err) => {
-                log::warn!("Failed to parse CPE ({cpe}): {err}");
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-    let to = to
-        .iter()
-        .map(|cpe| (cpe, Uri::parse(cpe)))
-        .filter_map(|(cpe, r)| match r {
-            Ok(uri) => Some(uri),
-            Err(err) => {
-                log::warn!("Failed to parse CPE ({cpe}): {err}");
-                None
-            }
-        })
-        .collect::<Vec<_>>();
+    println!(
+        "Products: {}, with matches: {hits}, without: {}",
+        correlations.len(),
+        correlations.len() - hits
+    );
 
+    Ok(())
+}
+
+fn print_resolutions(resolutions: Vec<Resolution>) -> (usize, usize) {
     let mut hits = 0;
     let mut misses = 0;
 
     println!(r#"VEX,Num,SBOMs"#);
 
-    for cpe in from {
-        let targets = to
-            .iter()
-            .filter(|c| matches(&cpe, c))
-            .map(|cpe| format!("{cpe}"))
-            .collect::<Vec<_>>();
-
-        let level = if targets.is_empty() {
+    for Resolution { source, targets } in resolutions {
+        if targets.is_empty() {
             misses += 1;
-            Level::Warn
         } else {
             hits += 1;
-            Level::Info
-        };
+        }
 
-        //log::log!(level, "{cpe} => [{}]", targets.join(", "));
-        println!(r#""{cpe}",{},"[{}]""#, targets.len(), targets.join(" "));
+        println!(r#""{source}",{},"[{}]""#, targets.len(), targets.join(" "));
     }
 
     (hits, misses)
 }
 
-macro_rules! match_ele {
-    ($a:expr, $b:expr, $t:ty, $name:ident) => {
-        if $a.$name() != <$t>::Any && $a.$name() != $b.$name() {
-            return false;
-        }
-    };
-    ($a:expr, $b:expr, $t:ty, *$name:ident) => {
-        if $a.$name() != &<$t>::Any && $a.$name() != $b.$name() {
-            return false;
-        }
-    };
-}
-
-macro_rules! match_comp {
-    ($a:expr, $b:expr, $name:ident) => {
-        match_ele!($a, $b, Component, $name)
-    };
-}
-
-fn matches(a: &Uri, b: &Uri) -> bool {
-    match_ele!(a, b, CpeType, part);
-    match_comp!(a, b, vendor);
-    match_comp!(a, b, product);
-    match_comp!(a, b, version);
-    match_comp!(a, b, update);
-    match_comp!(a, b, edition);
-    match_ele!(a, b, Language, *language);
-    match_comp!(a, b, sw_edition);
-    match_comp!(a, b, target_sw);
-    match_comp!(a, b, target_hw);
-    match_comp!(a, b, other);
-
-    true
-}
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let logger =
@@ -6,11 +6,9 @@ fn run(multi: &MultiProgress) -> anyhow::Result<()> {
     // run_task(multi, &mut tasks::unique_names::UniqueNames::default())?;
     // run_task(multi, &mut tasks::unique_main::UniqueMainPackages::default())?;
     run_task(multi, &mut tasks::main_cpe::MainCpe::default())?;
-    /*
-    run_task(
-        multi,
-        &mut tasks::main_cpe_db::MainCpeDb::new(playing_with_sboms::utils::cpe_dictionary::load(multi)?),
-    )?;*/
+    // run_task(multi, &mut tasks::all_cpe::AllCpe::default())?;
+    // run_task(multi, &mut tasks::validate_cpe::ValidateCpe::default())?;
+    // see the `main_cpe_db` binary for looking up the main CPEs in the dictionary
     Ok(())
 }
 
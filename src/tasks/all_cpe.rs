@@ -0,0 +1,99 @@
+use super::Task;
+use indicatif::ProgressBar;
+use spdx_rs::models::SPDX;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Coverage {
+    /// number of packages
+    pub packages: usize,
+    /// number of packages with at least one CPE
+    pub with_cpe: usize,
+}
+
+impl Coverage {
+    fn percent(&self) -> f64 {
+        if self.packages == 0 {
+            0.0
+        } else {
+            self.with_cpe as f64 * 100.0 / self.packages as f64
+        }
+    }
+}
+
+/// Collect the CPEs of all packages, not just the main ones
+#[derive(Default)]
+pub struct AllCpe {
+    pub main: Coverage,
+    pub dependencies: Coverage,
+    /// CPE, to number of occurrences as main package and as dependency
+    pub map: BTreeMap<String, (usize, usize)>,
+}
+
+impl Task for AllCpe {
+    fn process(&mut self, _progress: &ProgressBar, spdx: &SPDX) -> anyhow::Result<()> {
+        let main = spdx
+            .document_creation_information
+            .document_describes
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<BTreeSet<_>>();
+
+        for package in &spdx.package_information {
+            let is_main = main.contains(package.package_spdx_identifier.as_str());
+
+            let cpes = package
+                .external_reference
+                .iter()
+                .filter(|ext| {
+                    ext.reference_type == "cpe22Type" || ext.reference_type == "cpe23Type"
+                })
+                .map(|ext| &ext.reference_locator)
+                .collect::<BTreeSet<_>>();
+
+            let coverage = if is_main {
+                &mut self.main
+            } else {
+                &mut self.dependencies
+            };
+            coverage.packages += 1;
+            if !cpes.is_empty() {
+                coverage.with_cpe += 1;
+            }
+
+            for cpe in cpes {
+                let entry = self.map.entry(cpe.clone()).or_default();
+                if is_main {
+                    entry.0 += 1;
+                } else {
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for AllCpe {
+    fn drop(&mut self) {
+        println!("{} unique entries", self.map.len());
+        println!("CPE,Main,Dependency");
+        for (k, (main, deps)) in &self.map {
+            println!(r#""{k}",{main},{deps}"#);
+        }
+
+        println!(
+            "Main packages: {}, with CPE: {} ({:.1}%)",
+            self.main.packages,
+            self.main.with_cpe,
+            self.main.percent()
+        );
+        println!(
+            "Dependencies: {}, with CPE: {} ({:.1}%)",
+            self.dependencies.packages,
+            self.dependencies.with_cpe,
+            self.dependencies.percent()
+        );
+    }
+}
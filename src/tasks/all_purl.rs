@@ -0,0 +1,33 @@
+use super::Task;
+use indicatif::ProgressBar;
+use spdx_rs::models::SPDX;
+use std::collections::BTreeMap;
+
+/// Collect the purls of all packages
+#[derive(Default)]
+pub struct AllPurl {
+    pub map: BTreeMap<String, usize>,
+}
+
+impl Task for AllPurl {
+    fn process(&mut self, _progress: &ProgressBar, spdx: &SPDX) -> anyhow::Result<()> {
+        for package in &spdx.package_information {
+            for ext in &package.external_reference {
+                if ext.reference_type == "purl" {
+                    *self.map.entry(ext.reference_locator.clone()).or_default() += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for AllPurl {
+    fn drop(&mut self) {
+        println!("{} unique entries", self.map.len());
+        for (k, v) in &self.map {
+            println!("{k}: {v}");
+        }
+    }
+}
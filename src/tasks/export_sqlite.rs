@@ -0,0 +1,205 @@
+use super::Task;
+use crate::utils::vex::schema_name;
+use indicatif::ProgressBar;
+use rusqlite::{params, Connection, Transaction};
+use spdx_rs::models::SPDX;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS documents (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    namespace TEXT NOT NULL,
+    created TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS packages (
+    id INTEGER PRIMARY KEY,
+    document_id INTEGER NOT NULL REFERENCES documents(id),
+    spdx_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    version TEXT,
+    supplier TEXT,
+    download_location TEXT NOT NULL,
+    -- described by the document
+    main INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS external_references (
+    package_id INTEGER NOT NULL REFERENCES packages(id),
+    category TEXT NOT NULL,
+    type TEXT NOT NULL,
+    locator TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS checksums (
+    package_id INTEGER NOT NULL REFERENCES packages(id),
+    algorithm TEXT NOT NULL,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS licenses (
+    package_id INTEGER NOT NULL REFERENCES packages(id),
+    -- concluded or declared
+    kind TEXT NOT NULL,
+    expression TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS relationships (
+    document_id INTEGER NOT NULL REFERENCES documents(id),
+    element TEXT NOT NULL,
+    type TEXT NOT NULL,
+    related TEXT NOT NULL
+);
+-- exporting a document again replaces it
+CREATE UNIQUE INDEX IF NOT EXISTS documents_namespace ON documents(namespace);
+CREATE INDEX IF NOT EXISTS packages_document ON packages(document_id);
+CREATE INDEX IF NOT EXISTS packages_name ON packages(name);
+CREATE INDEX IF NOT EXISTS external_references_locator ON external_references(locator);
+"#;
+
+/// Export all SBOMs into an SQLite database
+pub struct ExportSbomSqlite {
+    pub connection: Connection,
+    documents: usize,
+}
+
+impl ExportSbomSqlite {
+    /// Create the tables, if they don't exist yet
+    pub fn new(connection: Connection) -> anyhow::Result<Self> {
+        // SQLite only enforces the `REFERENCES` of the schema when enabled, per connection
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection,
+            documents: 0,
+        })
+    }
+}
+
+/// Delete a previous export of a document, by its namespace
+fn delete_document(tx: &Transaction, namespace: &str) -> rusqlite::Result<()> {
+    let packages = "SELECT packages.id FROM packages
+        JOIN documents ON documents.id = packages.document_id
+        WHERE documents.namespace = ?1";
+
+    for table in ["external_references", "checksums", "licenses"] {
+        tx.execute(
+            &format!("DELETE FROM {table} WHERE package_id IN ({packages})"),
+            [namespace],
+        )?;
+    }
+    for table in ["packages", "relationships"] {
+        tx.execute(
+            &format!(
+                "DELETE FROM {table} WHERE document_id IN
+                (SELECT id FROM documents WHERE namespace = ?1)"
+            ),
+            [namespace],
+        )?;
+    }
+    tx.execute("DELETE FROM documents WHERE namespace = ?1", [namespace])?;
+
+    Ok(())
+}
+
+impl Task for ExportSbomSqlite {
+    fn process(&mut self, _: &ProgressBar, spdx: &SPDX) -> anyhow::Result<()> {
+        let info = &spdx.document_creation_information;
+
+        // one transaction per document, to keep the inserts fast
+        let tx = self.connection.transaction()?;
+
+        delete_document(&tx, &info.spdx_document_namespace)?;
+
+        tx.execute(
+            "INSERT INTO documents (name, namespace, created)
+            VALUES (?1, ?2, ?3)",
+            params![
+                info.document_name,
+                info.spdx_document_namespace,
+                info.creation_info.created.to_rfc3339()
+            ],
+        )?;
+        let document_id = tx.last_insert_rowid();
+
+        for package in &spdx.package_information {
+            tx.prepare_cached(
+                "INSERT INTO packages
+                (document_id, spdx_id, name, version, supplier, download_location, main)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?
+            .execute(params![
+                document_id,
+                package.package_spdx_identifier,
+                package.package_name,
+                package.package_version,
+                package.package_supplier,
+                package.package_download_location,
+                info.document_describes
+                    .contains(&package.package_spdx_identifier)
+            ])?;
+            let package_id = tx.last_insert_rowid();
+
+            for ext in &package.external_reference {
+                tx.prepare_cached(
+                    "INSERT INTO external_references (package_id, category, type, locator)
+                    VALUES (?1, ?2, ?3, ?4)",
+                )?
+                .execute(params![
+                    package_id,
+                    schema_name(&ext.reference_category),
+                    ext.reference_type,
+                    ext.reference_locator
+                ])?;
+            }
+
+            for checksum in &package.package_checksum {
+                tx.prepare_cached(
+                    "INSERT INTO checksums (package_id, algorithm, value)
+                    VALUES (?1, ?2, ?3)",
+                )?
+                .execute(params![
+                    package_id,
+                    schema_name(&checksum.algorithm),
+                    checksum.value
+                ])?;
+            }
+
+            for (kind, license) in [
+                ("concluded", &package.concluded_license),
+                ("declared", &package.declared_license),
+            ] {
+                if let Some(license) = license {
+                    tx.prepare_cached(
+                        "INSERT INTO licenses (package_id, kind, expression)
+                        VALUES (?1, ?2, ?3)",
+                    )?
+                    .execute(params![
+                        package_id,
+                        kind,
+                        license.to_string()
+                    ])?;
+                }
+            }
+        }
+
+        for relationship in &spdx.relationships {
+            tx.prepare_cached(
+                "INSERT INTO relationships (document_id, element, type, related)
+                VALUES (?1, ?2, ?3, ?4)",
+            )?
+            .execute(params![
+                document_id,
+                relationship.spdx_element_id,
+                schema_name(&relationship.relationship_type),
+                relationship.related_spdx_element
+            ])?;
+        }
+
+        tx.commit()?;
+        self.documents += 1;
+
+        Ok(())
+    }
+}
+
+impl Drop for ExportSbomSqlite {
+    fn drop(&mut self) {
+        println!("Exported {} SBOMs", self.documents);
+    }
+}
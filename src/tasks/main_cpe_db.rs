@@ -1,22 +1,34 @@
 use super::Task;
-use crate::utils::cpe_dictionary::CpeList;
+use crate::utils::cpe_dictionary::{CpeList, TitleFallback};
+use crate::utils::cpe_suggest::Suggester;
 use indicatif::ProgressBar;
 use spdx_rs::models::SPDX;
 use std::collections::{BTreeMap, BTreeSet};
 
 pub struct MainCpeDb {
     map: BTreeMap<String, usize>,
+    fallbacks: BTreeMap<TitleFallback, usize>,
+    missing: BTreeSet<String>,
     dictionary: CpeList,
+    lang: String,
 }
 
 impl MainCpeDb {
-    #[allow(unused)]
     pub fn new(dictionary: CpeList) -> Self {
         Self {
             map: Default::default(),
+            fallbacks: Default::default(),
+            missing: Default::default(),
             dictionary,
+            lang: "en-US".to_string(),
         }
     }
+
+    /// Set the preferred language for titles
+    pub fn with_lang(mut self, lang: impl Into<String>) -> Self {
+        self.lang = lang.into();
+        self
+    }
 }
 
 impl Task for MainCpeDb {
@@ -45,15 +57,17 @@ impl Task for MainCpeDb {
                         for ext in &package.external_reference {
                             if ext.reference_type == "cpe22Type" {
                                 let cpe = ext.reference_locator.clone();
-                                match self.dictionary.lookup_title(&cpe, "en_US") {
-                                    Some(title) => {
+                                match self.dictionary.lookup_title_with_fallback(&cpe, &self.lang) {
+                                    Some((title, fallback)) => {
                                         *self.map.entry(title.to_string()).or_default() += 1;
+                                        *self.fallbacks.entry(fallback).or_default() += 1;
                                     }
                                     None => {
                                         *self
                                             .map
                                             .entry("MISSING TITLE".to_string())
                                             .or_default() += 1;
+                                        self.missing.insert(cpe);
                                     }
                                 }
 
@@ -85,5 +99,21 @@ impl Drop for MainCpeDb {
         for (k, v) in &self.map {
             println!("{k}: {v}");
         }
+        println!("Title language ({}):", self.lang);
+        for (k, v) in &self.fallbacks {
+            println!("{k:?}: {v}");
+        }
+
+        println!("{} unknown CPEs", self.missing.len());
+        let suggester = Suggester::new(&self.dictionary);
+        for cpe in &self.missing {
+            println!("{cpe}:");
+            for s in suggester.suggest(cpe, 5) {
+                println!(
+                    "  {} ({:?}, distance: {})",
+                    s.item.name, s.reason, s.distance
+                );
+            }
+        }
     }
 }
@@ -1,10 +1,16 @@
 use indicatif::ProgressBar;
 use spdx_rs::models::SPDX;
 
+pub mod all_cpe;
+pub mod all_purl;
+pub mod export_sqlite;
 pub mod main_cpe;
 pub mod main_cpe_db;
+pub mod query;
 pub mod unique_main;
 pub mod unique_names;
+pub mod validate_cpe;
+pub mod vex;
 
 pub trait Task {
     fn process(&mut self, progress: &ProgressBar, sbom: &SPDX) -> anyhow::Result<()>;
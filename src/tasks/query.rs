@@ -0,0 +1,34 @@
+use super::Task;
+use crate::utils::query_lang::{Query, QueryResult};
+use indicatif::ProgressBar;
+use spdx_rs::models::SPDX;
+
+/// Evaluate a query against all SBOMs
+pub struct QuerySboms {
+    query: Query,
+    pub result: QueryResult,
+}
+
+impl QuerySboms {
+    pub fn new(query: Query) -> Self {
+        Self {
+            query,
+            result: Default::default(),
+        }
+    }
+}
+
+impl Task for QuerySboms {
+    fn process(&mut self, _: &ProgressBar, spdx: &SPDX) -> anyhow::Result<()> {
+        self.query
+            .evaluate(&serde_json::to_value(spdx)?, &mut self.result);
+
+        Ok(())
+    }
+}
+
+impl Drop for QuerySboms {
+    fn drop(&mut self) {
+        println!("{}", self.result);
+    }
+}
@@ -0,0 +1,101 @@
+use super::Task;
+use crate::utils::cpe_name::{self, Binding, Violation};
+use indicatif::ProgressBar;
+use spdx_rs::models::SPDX;
+use std::collections::BTreeMap;
+
+/// Validate all CPE references of all packages against the CPE specification
+#[derive(Default)]
+pub struct ValidateCpe {
+    /// number of CPEs checked
+    pub total: usize,
+    /// violations, per SBOM
+    pub sboms: BTreeMap<String, Vec<(String, Violation)>>,
+    /// kind of violation, per producer
+    pub producers: BTreeMap<String, BTreeMap<&'static str, usize>>,
+}
+
+/// The producer of an SBOM, taken from the organization creators, falling back to the tools.
+pub fn producer(spdx: &SPDX) -> String {
+    let creators = &spdx.document_creation_information.creation_info.creators;
+
+    let find = |prefix: &str| {
+        creators
+            .iter()
+            .filter_map(|c| c.strip_prefix(prefix))
+            .map(|c| c.trim())
+            .collect::<Vec<_>>()
+    };
+
+    let mut result = find("Organization:");
+    if result.is_empty() {
+        result = find("Tool:");
+    }
+
+    if result.is_empty() {
+        "UNKNOWN".to_string()
+    } else {
+        result.join(", ")
+    }
+}
+
+impl Task for ValidateCpe {
+    fn process(&mut self, _progress: &ProgressBar, spdx: &SPDX) -> anyhow::Result<()> {
+        let producer = producer(spdx);
+        let mut found = vec![];
+
+        for package in &spdx.package_information {
+            for ext in &package.external_reference {
+                let Some(declared) = Binding::from_reference_type(&ext.reference_type) else {
+                    continue;
+                };
+
+                self.total += 1;
+
+                for violation in cpe_name::validate(&ext.reference_locator, Some(declared)) {
+                    *self
+                        .producers
+                        .entry(producer.clone())
+                        .or_default()
+                        .entry(violation.kind())
+                        .or_default() += 1;
+                    found.push((ext.reference_locator.clone(), violation));
+                }
+            }
+        }
+
+        if !found.is_empty() {
+            self.sboms
+                .entry(spdx.document_creation_information.document_name.clone())
+                .or_default()
+                .extend(found);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ValidateCpe {
+    fn drop(&mut self) {
+        println!(
+            "{} CPEs checked, {} SBOMs with violations",
+            self.total,
+            self.sboms.len()
+        );
+
+        for (sbom, violations) in &self.sboms {
+            println!("{sbom}:");
+            for (cpe, violation) in violations {
+                println!("  {cpe}: {violation}");
+            }
+        }
+
+        println!("By producer:");
+        for (producer, kinds) in &self.producers {
+            println!("{producer}:");
+            for (k, v) in kinds {
+                println!("  {k}: {v}");
+            }
+        }
+    }
+}
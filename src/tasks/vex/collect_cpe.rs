@@ -0,0 +1,62 @@
+use crate::utils::vex::{product, ParsedAdvisory, VexTask};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use tokio::sync::Mutex;
+
+#[derive(Default)]
+pub struct CollectCpe {
+    pub map: Mutex<BTreeMap<String, usize>>,
+}
+
+#[async_trait]
+impl VexTask for CollectCpe {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        let cpes = product::collect_cpe(&advisory.csaf);
+
+        let mut map = self.map.lock().await;
+        for cpe in cpes {
+            *map.entry(cpe).or_default() += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for CollectCpe {
+    fn drop(&mut self) {
+        let map = self.map.get_mut();
+        println!("{} unique entries", map.len());
+        for (k, v) in map.iter() {
+            println!("{k}: {v}");
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CollectPurl {
+    pub map: Mutex<BTreeMap<String, usize>>,
+}
+
+#[async_trait]
+impl VexTask for CollectPurl {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        let purls = product::collect_purl(&advisory.csaf);
+
+        let mut map = self.map.lock().await;
+        for purl in purls {
+            *map.entry(purl).or_default() += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for CollectPurl {
+    fn drop(&mut self) {
+        let map = self.map.get_mut();
+        println!("{} unique entries", map.len());
+        for (k, v) in map.iter() {
+            println!("{k}: {v}");
+        }
+    }
+}
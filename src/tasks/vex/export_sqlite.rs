@@ -0,0 +1,156 @@
+use crate::utils::vex::product::ProductIndex;
+use crate::utils::vex::resolve::Product;
+use crate::utils::vex::status::vulnerability_status;
+use crate::utils::vex::{ParsedAdvisory, VexTask};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use rusqlite::{params, Connection};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS advisories (
+    id INTEGER PRIMARY KEY,
+    tracking_id TEXT NOT NULL,
+    version TEXT NOT NULL,
+    title TEXT NOT NULL,
+    publisher TEXT NOT NULL,
+    current_release_date TEXT NOT NULL,
+    url TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS vex_products (
+    advisory_id INTEGER NOT NULL REFERENCES advisories(id),
+    product_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    version TEXT,
+    cpe TEXT,
+    purl TEXT
+);
+CREATE TABLE IF NOT EXISTS vex_statuses (
+    advisory_id INTEGER NOT NULL REFERENCES advisories(id),
+    cve TEXT NOT NULL,
+    product_id TEXT NOT NULL,
+    status TEXT NOT NULL
+);
+-- exporting a revision of an advisory again replaces it
+CREATE UNIQUE INDEX IF NOT EXISTS advisories_revision ON advisories(tracking_id, version);
+CREATE INDEX IF NOT EXISTS vex_products_advisory ON vex_products(advisory_id);
+CREATE INDEX IF NOT EXISTS vex_statuses_advisory ON vex_statuses(advisory_id);
+CREATE INDEX IF NOT EXISTS vex_products_purl ON vex_products(purl);
+CREATE INDEX IF NOT EXISTS vex_products_cpe ON vex_products(cpe);
+CREATE INDEX IF NOT EXISTS vex_statuses_cve ON vex_statuses(cve);
+"#;
+
+/// Export the products and their status of all advisories into an SQLite database
+pub struct ExportCsafSqlite {
+    /// shared with the blocking threads doing the inserts
+    pub connection: Arc<Mutex<Connection>>,
+    advisories: AtomicUsize,
+}
+
+impl ExportCsafSqlite {
+    /// Create the tables, if they don't exist yet
+    pub fn new(connection: Connection) -> anyhow::Result<Self> {
+        // SQLite only enforces the `REFERENCES` of the schema when enabled, per connection
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            advisories: AtomicUsize::new(0),
+        })
+    }
+}
+
+/// Insert an advisory, its products and their status, in one transaction
+fn insert(connection: &mut Connection, advisory: &ParsedAdvisory) -> anyhow::Result<()> {
+    let csaf = &advisory.csaf;
+    let document = &csaf.document;
+    let index = ProductIndex::new(csaf);
+
+    let tx = connection.transaction()?;
+
+    // replace a previous export of the same revision
+    let previous = "SELECT id FROM advisories WHERE tracking_id = ?1 AND version = ?2";
+    for table in ["vex_products", "vex_statuses"] {
+        tx.execute(
+            &format!("DELETE FROM {table} WHERE advisory_id IN ({previous})"),
+            params![document.tracking.id, document.tracking.version],
+        )?;
+    }
+    tx.execute(
+        "DELETE FROM advisories WHERE tracking_id = ?1 AND version = ?2",
+        params![document.tracking.id, document.tracking.version],
+    )?;
+
+    tx.execute(
+        "INSERT INTO advisories
+        (tracking_id, version, title, publisher, current_release_date, url)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            document.tracking.id,
+            document.tracking.version,
+            document.title,
+            document.publisher.name,
+            document.tracking.current_release_date.to_rfc3339(),
+            advisory.url.as_str()
+        ],
+    )?;
+    let advisory_id = tx.last_insert_rowid();
+
+    for product in index.products() {
+        let product = Product::from_index(&index, &product.product_id.0);
+        tx.prepare_cached(
+            "INSERT INTO vex_products (advisory_id, product_id, name, version, cpe, purl)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?
+        .execute(params![
+            advisory_id,
+            product.product_id,
+            product.name,
+            product.version,
+            product.cpe,
+            product.purl.map(|purl| purl.to_string())
+        ])?;
+    }
+
+    for vulnerability in csaf.vulnerabilities.iter().flatten() {
+        let Some(cve) = &vulnerability.cve else {
+            continue;
+        };
+        for (product_id, status) in vulnerability_status(vulnerability) {
+            tx.prepare_cached(
+                "INSERT INTO vex_statuses (advisory_id, cve, product_id, status)
+                VALUES (?1, ?2, ?3, ?4)",
+            )?
+            .execute(params![advisory_id, cve, product_id, status.to_string()])?;
+        }
+    }
+
+    tx.commit()?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl VexTask for ExportCsafSqlite {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        // rusqlite is synchronous, so keep it off the async workers
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .map_err(|_| anyhow!("A previous insert panicked"))?;
+            insert(&mut connection, &advisory)
+        })
+        .await??;
+        self.advisories.fetch_add(1, Ordering::Relaxed);
+
+        Ok(())
+    }
+}
+
+impl Drop for ExportCsafSqlite {
+    fn drop(&mut self) {
+        println!("Exported {} advisories", self.advisories.get_mut());
+    }
+}
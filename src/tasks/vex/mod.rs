@@ -0,0 +1,8 @@
+//! Tasks working on VEX documents, see [`crate::utils::vex::VexTask`].
+
+pub mod collect_cpe;
+pub mod export_sqlite;
+pub mod query;
+pub mod revisions;
+pub mod statistics;
+pub mod validate_csaf;
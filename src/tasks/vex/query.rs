@@ -0,0 +1,54 @@
+use crate::utils::query_lang::{Query, QueryResult};
+use crate::utils::vex::{ParsedAdvisory, VexTask};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use tokio::sync::Mutex;
+
+/// Evaluate a query against all CSAF documents
+pub struct QueryCsaf {
+    query: Query,
+    /// the result of each document, by its URL, to merge them in a stable order
+    pub results: Mutex<BTreeMap<String, QueryResult>>,
+}
+
+impl QueryCsaf {
+    pub fn new(query: Query) -> Self {
+        Self {
+            query,
+            results: Default::default(),
+        }
+    }
+
+    /// The result over all documents
+    pub fn result(&mut self) -> QueryResult {
+        let mut result = QueryResult::default();
+        for document in self.results.get_mut().values() {
+            result.extend(document.clone());
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl VexTask for QueryCsaf {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        let query = self.query.clone();
+        let (url, result) = tokio::task::spawn_blocking(move || {
+            let document = serde_json::to_value(&advisory.csaf)?;
+            let mut result = QueryResult::default();
+            query.evaluate(&document, &mut result);
+            Ok::<_, anyhow::Error>((advisory.url.to_string(), result))
+        })
+        .await??;
+
+        self.results.lock().await.insert(url, result);
+
+        Ok(())
+    }
+}
+
+impl Drop for QueryCsaf {
+    fn drop(&mut self) {
+        println!("{}", self.result());
+    }
+}
@@ -0,0 +1,170 @@
+use crate::utils::version::Scheme;
+use crate::utils::vex::status::{vulnerability_status, Status};
+use crate::utils::vex::{ParsedAdvisory, VexTask};
+use async_trait::async_trait;
+use csaf::Csaf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use tokio::sync::Mutex;
+
+/// The statuses of a product, as a product can be listed more than once
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Statuses(pub BTreeSet<Status>);
+
+impl Display for Statuses {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("none");
+        }
+        for (n, status) in self.0.iter().enumerate() {
+            if n > 0 {
+                f.write_str("+")?;
+            }
+            write!(f, "{status}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The product statuses of one revision of an advisory
+#[derive(Clone, Debug)]
+pub struct Revision {
+    pub version: String,
+    /// the current release date, as a Unix timestamp
+    pub released: i64,
+    /// statuses, per CVE and product ID
+    pub statuses: BTreeMap<(String, String), Statuses>,
+}
+
+impl Revision {
+    pub fn new(csaf: &Csaf) -> Self {
+        let mut statuses = BTreeMap::<_, Statuses>::new();
+
+        for vulnerability in csaf.vulnerabilities.iter().flatten() {
+            let Some(cve) = &vulnerability.cve else {
+                continue;
+            };
+            for (product_id, status) in vulnerability_status(vulnerability) {
+                statuses
+                    .entry((cve.clone(), product_id.to_string()))
+                    .or_default()
+                    .0
+                    .insert(status);
+            }
+        }
+
+        let tracking = &csaf.document.tracking;
+        Self {
+            version: tracking.version.clone(),
+            released: tracking.current_release_date.timestamp(),
+            statuses,
+        }
+    }
+
+    /// Check if this revision is later than another one, by its current release date, and by its
+    /// version if both were released at the same time
+    pub fn is_later(&self, other: &Self) -> bool {
+        let scheme = match (
+            Scheme::detect(&self.version),
+            Scheme::detect(&other.version),
+        ) {
+            (Scheme::Semver, Scheme::Semver) => Scheme::Semver,
+            // integer versions compare numerically as well
+            _ => Scheme::Rpm,
+        };
+
+        self.released
+            .cmp(&other.released)
+            .then_with(|| scheme.compare(&self.version, &other.version))
+            .is_gt()
+    }
+}
+
+/// A change of the status of a product, between two revisions of an advisory
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusChange {
+    pub advisory: String,
+    pub cve: String,
+    pub product_id: String,
+    pub before: Statuses,
+    pub after: Statuses,
+}
+
+impl Display for StatusChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}: {} -> {}",
+            self.advisory, self.cve, self.product_id, self.before, self.after
+        )
+    }
+}
+
+/// Collect the latest revision of each advisory, by its tracking ID
+#[derive(Default)]
+pub struct Revisions {
+    pub advisories: Mutex<BTreeMap<String, Revision>>,
+}
+
+#[async_trait]
+impl VexTask for Revisions {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        let id = advisory.csaf.document.tracking.id.clone();
+        let revision = Revision::new(&advisory.csaf);
+
+        let mut advisories = self.advisories.lock().await;
+        match advisories.get(&id) {
+            Some(existing) if !revision.is_later(existing) => {
+                log::debug!(
+                    "Skipping revision {} of {id}, already have {}",
+                    revision.version,
+                    existing.version
+                );
+            }
+            _ => {
+                advisories.insert(id, revision);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// All status changes between two snapshots, including products appearing and disappearing.
+///
+/// Advisories only present in one of the snapshots are not compared.
+pub fn status_changes(
+    before: &BTreeMap<String, Revision>,
+    after: &BTreeMap<String, Revision>,
+) -> Vec<StatusChange> {
+    let mut result = vec![];
+
+    for (advisory, old) in before {
+        let Some(new) = after.get(advisory) else {
+            continue;
+        };
+
+        let keys = old
+            .statuses
+            .keys()
+            .chain(new.statuses.keys())
+            .collect::<BTreeSet<_>>();
+
+        for key in keys {
+            let before = old.statuses.get(key).cloned().unwrap_or_default();
+            let after = new.statuses.get(key).cloned().unwrap_or_default();
+            if before != after {
+                let (cve, product_id) = key.clone();
+                result.push(StatusChange {
+                    advisory: advisory.clone(),
+                    cve,
+                    product_id,
+                    before,
+                    after,
+                });
+            }
+        }
+    }
+
+    result
+}
@@ -0,0 +1,175 @@
+use crate::utils::vex::product::ProductIndex;
+use crate::utils::vex::statement::{Format, VexDocument};
+use crate::utils::vex::status::{vulnerability_status, Status};
+use crate::utils::vex::{schema_name, ParsedAdvisory, VexTask};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use tokio::sync::Mutex;
+
+#[derive(Default)]
+pub struct Counts {
+    pub total: usize,
+    /// advisories, per year of the initial release
+    pub years: BTreeMap<String, usize>,
+    pub publishers: BTreeMap<String, usize>,
+    /// advisories, per TLP label
+    pub tlp: BTreeMap<String, usize>,
+    /// advisories, per number of CVEs
+    pub cves: BTreeMap<usize, usize>,
+    /// product statuses, over all vulnerabilities
+    pub statuses: BTreeMap<Status, usize>,
+    /// usage of flag labels, justifying `not_affected`
+    pub justifications: BTreeMap<String, usize>,
+    /// advisories, per number of products
+    pub products: BTreeMap<usize, usize>,
+    /// advisories, per number of revisions
+    pub revisions: BTreeMap<usize, usize>,
+}
+
+/// Aggregate numbers over all advisories, straight from the CSAF data
+#[derive(Default)]
+pub struct Statistics {
+    pub counts: Mutex<Counts>,
+}
+
+#[async_trait]
+impl VexTask for Statistics {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        let csaf = &advisory.csaf;
+        let document = &csaf.document;
+
+        let year = document
+            .tracking
+            .initial_release_date
+            .format("%Y")
+            .to_string();
+        let tlp = document
+            .distribution
+            .as_ref()
+            .and_then(|d| d.tlp.as_ref())
+            .map(|tlp| schema_name(&tlp.label))
+            .unwrap_or_else(|| "none".to_string());
+        let vulnerabilities = csaf.vulnerabilities.as_deref().unwrap_or_default();
+        let cves = vulnerabilities.iter().filter(|v| v.cve.is_some()).count();
+        let products = ProductIndex::new(csaf).products().count();
+
+        let mut counts = self.counts.lock().await;
+
+        counts.total += 1;
+        *counts.years.entry(year).or_default() += 1;
+        *counts
+            .publishers
+            .entry(document.publisher.name.clone())
+            .or_default() += 1;
+        *counts.tlp.entry(tlp).or_default() += 1;
+        *counts.cves.entry(cves).or_default() += 1;
+        for vulnerability in vulnerabilities {
+            for (_, status) in vulnerability_status(vulnerability) {
+                *counts.statuses.entry(status).or_default() += 1;
+            }
+            for flag in vulnerability.flags.iter().flatten() {
+                *counts
+                    .justifications
+                    .entry(schema_name(&flag.label))
+                    .or_default() += 1;
+            }
+        }
+        *counts.products.entry(products).or_default() += 1;
+        *counts
+            .revisions
+            .entry(document.tracking.revision_history.len())
+            .or_default() += 1;
+
+        Ok(())
+    }
+}
+
+/// Numbers of the statements of documents of all formats, CSAF, OpenVEX and CycloneDX
+#[derive(Default)]
+pub struct StatementCounts {
+    /// documents, per format
+    pub formats: BTreeMap<Format, usize>,
+    /// statements, per format and status
+    pub statements: BTreeMap<(Format, Status), usize>,
+    /// justifications of statements, per format
+    pub justifications: BTreeMap<(Format, String), usize>,
+}
+
+/// Aggregate numbers over the statements of all documents.
+///
+/// Converting CSAF advisories into statements resolves all their products, so this is more
+/// expensive than [`Statistics`].
+#[derive(Default)]
+pub struct StatementStatistics {
+    pub counts: Mutex<StatementCounts>,
+}
+
+#[async_trait]
+impl VexTask for StatementStatistics {
+    const WANTS_STATEMENTS: bool = true;
+
+    async fn process(&self, _advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn process_statements(&self, document: &VexDocument) -> anyhow::Result<()> {
+        let mut counts = self.counts.lock().await;
+
+        *counts.formats.entry(document.format).or_default() += 1;
+
+        for statement in &document.statements {
+            *counts
+                .statements
+                .entry((document.format, statement.status))
+                .or_default() += 1;
+            if let Some(justification) = &statement.justification {
+                *counts
+                    .justifications
+                    .entry((document.format, justification.clone()))
+                    .or_default() += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn print<K: std::fmt::Display>(title: &str, map: &BTreeMap<K, usize>) {
+    println!("{title}:");
+    for (k, v) in map {
+        println!("  {k}: {v}");
+    }
+}
+
+impl Drop for Statistics {
+    fn drop(&mut self) {
+        let counts = self.counts.get_mut();
+
+        println!("{} advisories", counts.total);
+
+        print("Per year", &counts.years);
+        print("Per publisher", &counts.publishers);
+        print("Per TLP", &counts.tlp);
+        print("CVEs per advisory", &counts.cves);
+        print("Product status", &counts.statuses);
+        print("Justifications", &counts.justifications);
+        print("Products per advisory", &counts.products);
+        print("Revisions per advisory", &counts.revisions);
+    }
+}
+
+impl Drop for StatementStatistics {
+    fn drop(&mut self) {
+        let counts = self.counts.get_mut();
+
+        print("Documents per format", &counts.formats);
+        println!("Statements per format:");
+        for ((format, status), count) in &counts.statements {
+            println!("  {format} {status}: {count}");
+        }
+        println!("Justifications per format:");
+        for ((format, justification), count) in &counts.justifications {
+            println!("  {format} {justification}: {count}");
+        }
+    }
+}
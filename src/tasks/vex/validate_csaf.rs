@@ -0,0 +1,78 @@
+use crate::utils::vex::validate::{self, Violation};
+use crate::utils::vex::{ParsedAdvisory, VexTask};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use tokio::sync::Mutex;
+
+#[derive(Default)]
+pub struct Validation {
+    /// number of advisories checked
+    pub total: usize,
+    /// violations, per advisory
+    pub advisories: BTreeMap<String, Vec<Violation>>,
+    /// number of violations, per test
+    pub tests: BTreeMap<(&'static str, &'static str), usize>,
+}
+
+/// Run the mandatory tests of CSAF 2.0 on all advisories
+#[derive(Default)]
+pub struct ValidateCsaf {
+    pub validation: Mutex<Validation>,
+}
+
+#[async_trait]
+impl VexTask for ValidateCsaf {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        // the tests walk the whole document, so keep them off the async workers
+        let (id, violations) = tokio::task::spawn_blocking(move || {
+            let violations = validate::validate(&advisory.csaf);
+            (advisory.csaf.document.tracking.id, violations)
+        })
+        .await?;
+
+        let mut validation = self.validation.lock().await;
+        validation.total += 1;
+
+        for violation in &violations {
+            *validation
+                .tests
+                .entry((violation.test(), violation.kind()))
+                .or_default() += 1;
+        }
+
+        if !violations.is_empty() {
+            validation
+                .advisories
+                .entry(id)
+                .or_default()
+                .extend(violations);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ValidateCsaf {
+    fn drop(&mut self) {
+        let validation = self.validation.get_mut();
+
+        println!(
+            "{} advisories checked, {} conform, {} with violations",
+            validation.total,
+            validation.total.saturating_sub(validation.advisories.len()),
+            validation.advisories.len()
+        );
+
+        for (advisory, violations) in &validation.advisories {
+            println!("{advisory}:");
+            for violation in violations {
+                println!("  {violation}");
+            }
+        }
+
+        println!("By test:");
+        for ((test, kind), count) in &validation.tests {
+            println!("  {test} {kind}: {count}");
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! Correlate VEX products with SBOM packages.
+
+use crate::run::run_task;
+use crate::tasks::Task;
+use crate::utils::cpe_match::{self, VersionMatch};
+use crate::utils::cpe_name::Wfn;
+use crate::utils::purl::Purl;
+use crate::utils::vex::identify::{match_packages, Evidence, Helpers, SbomIndex};
+use crate::utils::vex::product::ProductIndex;
+use crate::utils::vex::{run_vex, ParsedAdvisory, VexTask};
+use async_trait::async_trait;
+use csaf::Csaf;
+use indicatif::{MultiProgress, ProgressBar};
+use spdx_rs::models::SPDX;
+use std::collections::{BTreeMap, BTreeSet};
+use tokio::sync::Mutex;
+
+/// A product of a VEX document
+#[derive(Clone, Debug)]
+pub struct VexProduct {
+    /// The tracking ID of the advisory
+    pub advisory: String,
+    pub product_id: String,
+    pub name: String,
+    pub helpers: Helpers,
+}
+
+/// A package of an SBOM, matching a VEX product
+#[derive(Clone, Debug)]
+pub struct PackageMatch {
+    /// The name of the SBOM document
+    pub sbom: String,
+    /// The namespace of the SBOM document
+    pub namespace: String,
+    pub spdx_id: String,
+    pub package_name: String,
+    /// If the package is one of the main packages of the SBOM
+    pub main: bool,
+    /// Why the package matched
+    pub evidence: BTreeSet<Evidence>,
+    pub score: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Correlation {
+    pub product: VexProduct,
+    /// The matching packages, best match first
+    pub matches: Vec<PackageMatch>,
+}
+
+impl Correlation {
+    /// The names of all SBOMs with a matching package
+    pub fn sboms(&self) -> BTreeSet<&str> {
+        self.matches.iter().map(|m| m.sbom.as_str()).collect()
+    }
+}
+
+/// Collect all VEX products which carry identification helpers, as well as all CPEs and purls
+#[derive(Default)]
+pub struct CollectProducts {
+    pub products: Mutex<Vec<VexProduct>>,
+    /// CPEs, to the number of advisories using them
+    pub cpes: Mutex<BTreeMap<String, usize>>,
+    /// purls, to the number of advisories using them
+    pub purls: Mutex<BTreeMap<String, usize>>,
+}
+
+impl CollectProducts {
+    /// The products, in a stable order, as advisories are processed concurrently
+    pub fn into_products(self) -> Vec<VexProduct> {
+        let mut products = self.products.into_inner();
+        products.sort_by(|a, b| (&a.advisory, &a.product_id).cmp(&(&b.advisory, &b.product_id)));
+        products
+    }
+}
+
+/// the products of an advisory which carry identification helpers, and its CPEs and purls
+fn vex_products(csaf: &Csaf) -> (Vec<VexProduct>, BTreeSet<String>, BTreeSet<String>) {
+    let index = ProductIndex::new(csaf);
+    let mut products = vec![];
+    let mut cpes = BTreeSet::new();
+    let mut purls = BTreeSet::new();
+
+    for product in index.products() {
+        if let Some(helper) = &product.product_identification_helper {
+            cpes.extend(helper.cpe.iter().map(|cpe| cpe.to_string()));
+            purls.extend(helper.purl.iter().map(|purl| purl.to_string()));
+        }
+
+        let helpers = Helpers::from_product(product);
+        if helpers.is_empty() {
+            continue;
+        }
+        products.push(VexProduct {
+            advisory: csaf.document.tracking.id.clone(),
+            product_id: product.product_id.0.clone(),
+            name: product.name.clone(),
+            helpers,
+        });
+    }
+
+    (products, cpes, purls)
+}
+
+#[async_trait]
+impl VexTask for CollectProducts {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        // parsing all helpers is CPU bound
+        let (products, cpes, purls) =
+            tokio::task::spawn_blocking(move || vex_products(&advisory.csaf)).await?;
+
+        self.products.lock().await.extend(products);
+        for (map, values) in [(&self.cpes, cpes), (&self.purls, purls)] {
+            let mut map = map.lock().await;
+            for value in values {
+                *map.entry(value).or_default() += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Match a set of VEX products against every SBOM
+pub struct Correlate {
+    pub correlations: Vec<Correlation>,
+}
+
+impl Correlate {
+    pub fn new(products: impl IntoIterator<Item = VexProduct>) -> Self {
+        Self {
+            correlations: products
+                .into_iter()
+                .map(|product| Correlation {
+                    product,
+                    matches: vec![],
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Task for Correlate {
+    fn process(&mut self, _progress: &ProgressBar, spdx: &SPDX) -> anyhow::Result<()> {
+        let info = &spdx.document_creation_information;
+        let sbom = SbomIndex::new(spdx);
+
+        for correlation in &mut self.correlations {
+            for m in match_packages(&correlation.product.helpers, &sbom) {
+                correlation.matches.push(PackageMatch {
+                    sbom: info.document_name.clone(),
+                    namespace: info.spdx_document_namespace.clone(),
+                    spdx_id: m.package.package_spdx_identifier.clone(),
+                    package_name: m.package.package_name.clone(),
+                    main: m.main,
+                    evidence: m.evidence,
+                    score: m.score,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Correlate {
+    /// The correlations, with the best matches first
+    pub fn into_correlations(self) -> Vec<Correlation> {
+        let mut correlations = self.correlations;
+        for correlation in &mut correlations {
+            correlation
+                .matches
+                .sort_by_key(|m| std::cmp::Reverse(m.score));
+        }
+        correlations
+    }
+}
+
+/// Correlate all VEX products with all SBOMs
+pub async fn correlate(multi: &MultiProgress) -> anyhow::Result<Vec<Correlation>> {
+    let products = run_vex(multi, CollectProducts::default()).await?;
+
+    let mut correlate = Correlate::new(products.into_products());
+    run_task(multi, &mut correlate)?;
+
+    Ok(correlate.into_correlations())
+}
+
+/// An identifier from a VEX document, and the SBOM identifiers it matched
+#[derive(Clone, Debug)]
+pub struct Resolution {
+    pub source: String,
+    pub targets: Vec<String>,
+}
+
+/// fix `*` in URIs, which isn't valid, to "ANY"
+pub fn fixup_cpe(cpe: String) -> String {
+    match cpe.strip_prefix("cpe:/") {
+        Some(rest) => format!(
+            "cpe:/{}",
+            rest.split(':')
+                .map(|c| if c == "*" { "" } else { c })
+                .collect::<Vec<_>>()
+                .join(":")
+        ),
+        None => cpe,
+    }
+}
+
+fn parse_cpes<I>(cpes: I) -> Vec<(String, Wfn)>
+where
+    I: IntoIterator<Item = String>,
+{
+    cpes.into_iter()
+        .map(fixup_cpe)
+        .filter_map(|cpe| match Wfn::parse(&cpe) {
+            Ok(wfn) => Some((cpe, wfn)),
+            Err(err) => {
+                log::warn!("Failed to parse CPE ({cpe}): {err:?}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Resolve VEX CPEs against SBOM CPEs
+pub fn resolve_cpes<F, T>(from: F, to: T) -> Vec<Resolution>
+where
+    F: IntoIterator<Item = String>,
+    T: IntoIterator<Item = String>,
+{
+    let to = parse_cpes(to);
+
+    parse_cpes(from)
+        .into_iter()
+        .map(|(source, wfn)| Resolution {
+            targets: to
+                .iter()
+                .filter(|(_, c)| cpe_match::is_match_with(&wfn, c, &VersionMatch::Prefix))
+                .map(|(cpe, _)| cpe.clone())
+                .collect(),
+            source,
+        })
+        .collect()
+}
+
+fn parse_purls<I>(purls: I) -> Vec<Purl>
+where
+    I: IntoIterator<Item = String>,
+{
+    purls
+        .into_iter()
+        .filter_map(|purl| match Purl::parse(&purl) {
+            Ok(purl) => Some(purl),
+            Err(err) => {
+                log::warn!("Failed to parse purl ({purl}): {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Resolve VEX purls against SBOM purls
+pub fn resolve_purls<F, T>(from: F, to: T) -> Vec<Resolution>
+where
+    F: IntoIterator<Item = String>,
+    T: IntoIterator<Item = String>,
+{
+    let to = parse_purls(to);
+
+    parse_purls(from)
+        .into_iter()
+        .map(|purl| Resolution {
+            source: purl.to_string(),
+            targets: to
+                .iter()
+                .filter(|p| purl.matches(p))
+                .map(|p| p.to_string())
+                .collect(),
+        })
+        .collect()
+}
@@ -1,7 +1,11 @@
+use anyhow::anyhow;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use std::io::BufReader;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader};
 
-#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct Generator {
     pub product_name: String,
     pub product_version: String,
@@ -32,7 +36,86 @@ pub struct CpeList {
     pub items: Vec<CpeItem>,
 }
 
+/// Restricts what gets loaded from the dictionary.
+///
+/// An empty set accepts everything for that field. Languages match either the full tag or the
+/// primary language, so `en` keeps `en-US` titles.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    pub parts: BTreeSet<String>,
+    pub vendors: BTreeSet<String>,
+    pub products: BTreeSet<String>,
+    pub languages: BTreeSet<String>,
+}
+
+/// a comma separated set from the environment
+fn env_set(name: &str) -> BTreeSet<String> {
+    std::env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string())
+        .collect()
+}
+
+impl Filter {
+    /// Read the filter from `CPE_PARTS`, `CPE_VENDORS`, `CPE_PRODUCTS` and `CPE_LANGUAGES`, all
+    /// comma separated lists.
+    pub fn from_env() -> Self {
+        Self {
+            parts: env_set("CPE_PARTS"),
+            vendors: env_set("CPE_VENDORS"),
+            products: env_set("CPE_PRODUCTS"),
+            languages: env_set("CPE_LANGUAGES"),
+        }
+    }
+
+    fn accepts_name(&self, name: &str) -> bool {
+        if self.parts.is_empty() && self.vendors.is_empty() && self.products.is_empty() {
+            return true;
+        }
+
+        let Some((part, vendor, product)) = split_name(name) else {
+            return false;
+        };
+
+        (self.parts.is_empty() || self.parts.contains(part))
+            && (self.vendors.is_empty() || self.vendors.contains(vendor))
+            && (self.products.is_empty() || self.products.contains(product))
+    }
+
+    fn accepts_lang(&self, lang: &str) -> bool {
+        if self.languages.is_empty() {
+            return true;
+        }
+
+        let primary = primary_language(lang);
+        self.languages
+            .iter()
+            .any(|accepted| same_tag(accepted, lang) || same_tag(accepted, primary))
+    }
+}
+
+/// split a CPE name (2.2 URI or 2.3 formatted string) into part, vendor and product
+fn split_name(name: &str) -> Option<(&str, &str, &str)> {
+    let rest = name
+        .strip_prefix("cpe:2.3:")
+        .or_else(|| name.strip_prefix("cpe:/"))?;
+
+    let mut s = rest.split(':');
+    let part = s.next()?;
+    let vendor = s.next().unwrap_or_default();
+    let product = s.next().unwrap_or_default();
+
+    Some((part, vendor, product))
+}
+
 pub fn load(multi: &MultiProgress) -> anyhow::Result<CpeList> {
+    load_filtered(multi, &Filter::default())
+}
+
+pub fn load_filtered(multi: &MultiProgress, filter: &Filter) -> anyhow::Result<CpeList> {
     log::info!("Loading CPE dictionary");
 
     let file = std::fs::File::open("data/official-cpe-dictionary_v2.3.xml.gz")?;
@@ -43,24 +126,296 @@ pub fn load(multi: &MultiProgress) -> anyhow::Result<CpeList> {
         .with_message("Loading CPE database");
 
     let decoder = flate2::read::GzDecoder::new(BufReader::new(progress.wrap_read(file)));
-    let list: CpeList = quick_xml::de::from_reader(BufReader::new(decoder))?;
+    let list = parse(BufReader::new(decoder), filter)?;
+
+    progress.finish();
 
     log::info!("Loaded {} definitions", list.items.len());
 
     Ok(list)
 }
 
+/// Stream the dictionary, only keeping the items and titles accepted by the filter.
+pub fn parse<R: BufRead>(reader: R, filter: &Filter) -> anyhow::Result<CpeList> {
+    let mut reader = Reader::from_reader(reader);
+    reader.trim_text(true);
+
+    let mut generator = Generator::default();
+    let mut items = vec![];
+
+    // the element path below the root, only tracked as far as we care
+    let mut path: Vec<Vec<u8>> = vec![];
+    let mut item: Option<CpeItem> = None;
+    let mut lang: Option<String> = None;
+
+    let mut buf = vec![];
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => {
+                let name = e.local_name().as_ref().to_vec();
+                match name.as_slice() {
+                    b"cpe-item" => item = start_item(&e, filter)?,
+                    b"title" if item.is_some() => {
+                        lang = attribute(&e, b"lang")?.filter(|lang| filter.accepts_lang(lang));
+                    }
+                    _ => {}
+                }
+                path.push(name);
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"cpe-item" => {
+                items.extend(start_item(&e, filter)?);
+            }
+            Event::Text(e) => {
+                let text = e.unescape()?;
+                match path.as_slice() {
+                    [_, g, field] if g == b"generator" => match field.as_slice() {
+                        b"product_name" => generator.product_name = text.into_owned(),
+                        b"product_version" => generator.product_version = text.into_owned(),
+                        b"schema_version" => generator.schema_version = text.into_owned(),
+                        b"timestamp" => generator.timestamp = text.into_owned(),
+                        _ => {}
+                    },
+                    [_, i, t] if i == b"cpe-item" && t == b"title" => {
+                        if let (Some(item), Some(lang)) = (&mut item, lang.take()) {
+                            item.title.push(Title {
+                                lang,
+                                title: text.into_owned(),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(e) => {
+                path.pop();
+                if e.local_name().as_ref() == b"cpe-item" {
+                    items.extend(item.take());
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(CpeList { generator, items })
+}
+
+fn start_item(e: &BytesStart, filter: &Filter) -> anyhow::Result<Option<CpeItem>> {
+    let name = attribute(e, b"name")?.ok_or_else(|| anyhow!("cpe-item without a name"))?;
+
+    Ok(filter.accepts_name(&name).then(|| CpeItem {
+        name,
+        title: vec![],
+    }))
+}
+
+fn attribute(e: &BytesStart, name: &[u8]) -> anyhow::Result<Option<String>> {
+    for attr in e.attributes() {
+        let attr = attr?;
+        if attr.key.local_name().as_ref() == name {
+            return Ok(Some(attr.unescape_value()?.into_owned()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Which step of the language fallback produced a title.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TitleFallback {
+    /// The requested tag matched
+    Exact,
+    /// Only the primary language of the requested tag matched
+    PrimaryLanguage,
+    /// Some other title was used
+    Any,
+}
+
+/// compare language tags, ignoring case and treating `_` like `-`
+fn same_tag(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.chars().zip(b.chars()).all(|(a, b)| match (a, b) {
+            ('-' | '_', '-' | '_') => true,
+            (a, b) => a.eq_ignore_ascii_case(&b),
+        })
+}
+
+fn primary_language(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+impl CpeItem {
+    /// Find the best title for a language tag.
+    ///
+    /// This tries the exact tag first, then the primary language (`en` for `en-US`, including
+    /// other regions like `en-GB`), and finally falls back to the first title.
+    pub fn title(&self, lang: &str) -> Option<(&str, TitleFallback)> {
+        if let Some(title) = self.title.iter().find(|t| same_tag(&t.lang, lang)) {
+            return Some((&title.title, TitleFallback::Exact));
+        }
+
+        let primary = primary_language(lang);
+        if let Some(title) = self
+            .title
+            .iter()
+            .find(|t| same_tag(primary_language(&t.lang), primary))
+        {
+            return Some((&title.title, TitleFallback::PrimaryLanguage));
+        }
+
+        self.title
+            .first()
+            .map(|title| (title.title.as_str(), TitleFallback::Any))
+    }
+}
+
 impl CpeList {
     pub fn lookup(&self, cpe: &str) -> Option<&CpeItem> {
         self.items.iter().find(|item| item.name == cpe)
     }
 
     pub fn lookup_title(&self, cpe: &str, lang: &str) -> Option<&str> {
-        self.lookup(cpe).and_then(|cpe| {
-            cpe.title
+        self.lookup_title_with_fallback(cpe, lang)
+            .map(|(title, _)| title)
+    }
+
+    /// Like [`Self::lookup_title`], but also reports which fallback was used.
+    pub fn lookup_title_with_fallback(
+        &self,
+        cpe: &str,
+        lang: &str,
+    ) -> Option<(&str, TitleFallback)> {
+        self.lookup(cpe).and_then(|cpe| cpe.title(lang))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DICTIONARY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<cpe-list xmlns="http://cpe.mitre.org/dictionary/2.0">
+  <generator>
+    <product_name>National Vulnerability Database (NVD)</product_name>
+    <product_version>4.9</product_version>
+    <schema_version>2.3</schema_version>
+    <timestamp>2023-06-01T03:50:00.000Z</timestamp>
+  </generator>
+  <cpe-item name="cpe:/a:redhat:openssl:1.1.1k">
+    <title xml:lang="en-US">Red Hat OpenSSL 1.1.1k</title>
+    <title xml:lang="de-DE">Red Hat OpenSSL 1.1.1k (deutsch)</title>
+  </cpe-item>
+  <cpe-item name="cpe:/o:redhat:enterprise_linux:8"/>
+  <cpe-item name="cpe:/a:gnu:zlib:1.2.11">
+    <title xml:lang="en_us">zlib 1.2.11</title>
+  </cpe-item>
+</cpe-list>
+"#;
+
+    fn parse_with(filter: &Filter) -> CpeList {
+        parse(DICTIONARY.as_bytes(), filter).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let list = parse_with(&Filter::default());
+
+        assert_eq!(list.generator.product_version, "4.9");
+        assert_eq!(list.generator.timestamp, "2023-06-01T03:50:00.000Z");
+        assert_eq!(
+            list.items
                 .iter()
-                .find(|title| title.lang == lang)
-                .map(|title| title.title.as_str())
-        })
+                .map(|i| i.name.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "cpe:/a:redhat:openssl:1.1.1k",
+                "cpe:/o:redhat:enterprise_linux:8",
+                "cpe:/a:gnu:zlib:1.2.11"
+            ]
+        );
+        assert_eq!(list.items[0].title.len(), 2);
+        assert!(list.items[1].title.is_empty());
+    }
+
+    #[test]
+    fn test_filter_name() {
+        let filter = Filter {
+            vendors: BTreeSet::from(["redhat".to_string()]),
+            parts: BTreeSet::from(["a".to_string()]),
+            ..Default::default()
+        };
+        let list = parse_with(&filter);
+
+        assert_eq!(list.items.len(), 1);
+        assert_eq!(list.items[0].name, "cpe:/a:redhat:openssl:1.1.1k");
+    }
+
+    #[test]
+    fn test_filter_from_env() {
+        std::env::set_var("CPE_VENDORS", "redhat, gnu");
+        std::env::set_var("CPE_LANGUAGES", "en");
+        let filter = Filter::from_env();
+        std::env::remove_var("CPE_VENDORS");
+        std::env::remove_var("CPE_LANGUAGES");
+
+        assert_eq!(
+            filter.vendors,
+            BTreeSet::from(["gnu".to_string(), "redhat".to_string()])
+        );
+        assert_eq!(filter.languages, BTreeSet::from(["en".to_string()]));
+        assert!(filter.parts.is_empty());
+        assert!(filter.products.is_empty());
+    }
+
+    #[test]
+    fn test_filter_language() {
+        // case and separator don't matter, the primary language matches all regions
+        for lang in ["en-us", "EN_US", "en"] {
+            let filter = Filter {
+                languages: BTreeSet::from([lang.to_string()]),
+                ..Default::default()
+            };
+            let list = parse_with(&filter);
+
+            assert_eq!(
+                list.items[0]
+                    .title
+                    .iter()
+                    .map(|t| t.lang.as_str())
+                    .collect::<Vec<_>>(),
+                ["en-US"],
+                "{lang}"
+            );
+            assert_eq!(list.items[2].title.len(), 1, "{lang}");
+        }
+    }
+
+    #[test]
+    fn test_title_fallback() {
+        let list = parse_with(&Filter::default());
+        let openssl = "cpe:/a:redhat:openssl:1.1.1k";
+
+        assert_eq!(
+            list.lookup_title_with_fallback(openssl, "de_de"),
+            Some(("Red Hat OpenSSL 1.1.1k (deutsch)", TitleFallback::Exact))
+        );
+        assert_eq!(
+            list.lookup_title_with_fallback(openssl, "en-GB"),
+            Some(("Red Hat OpenSSL 1.1.1k", TitleFallback::PrimaryLanguage))
+        );
+        assert_eq!(
+            list.lookup_title_with_fallback(openssl, "fr-FR"),
+            Some(("Red Hat OpenSSL 1.1.1k", TitleFallback::Any))
+        );
+        assert_eq!(
+            list.lookup_title("cpe:/a:gnu:zlib:1.2.11", "en-US"),
+            Some("zlib 1.2.11")
+        );
+        assert_eq!(
+            list.lookup_title("cpe:/o:redhat:enterprise_linux:8", "en-US"),
+            None
+        );
+        assert_eq!(list.lookup_title("cpe:/a:unknown:unknown", "en-US"), None);
     }
 }
@@ -0,0 +1,384 @@
+//! CPE name matching, following the CPE 2.3 name matching specification (NISTIR 7696).
+//!
+//! The source name may contain wildcards, the target name must not. Comparing a source with a
+//! target which does contain wildcards yields [`Relation::Undefined`].
+
+use crate::utils::cpe_name::{Value, Wfn};
+use crate::utils::version::{self, Scheme, VersionRange};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Relation {
+    /// The source matches (at least) everything the target does
+    Superset,
+    /// The target matches everything the source does
+    Subset,
+    Equal,
+    Disjoint,
+    /// No relation can be determined, e.g. because the target contains wildcards
+    Undefined,
+}
+
+/// lower-cased characters, with their quoted flag
+fn normalize(value: &str) -> Vec<(char, bool)> {
+    Value::chars(value)
+        .into_iter()
+        .map(|(c, quoted)| (c.to_ascii_lowercase(), quoted))
+        .collect()
+}
+
+fn has_wildcards(value: &[(char, bool)]) -> bool {
+    value
+        .iter()
+        .any(|(c, quoted)| !quoted && (*c == '*' || *c == '?'))
+}
+
+/// Compare a single attribute value
+pub fn compare_value(source: &Value, target: &Value) -> Relation {
+    let (source, target) = match (source, target) {
+        (_, Value::Value(t)) if has_wildcards(&normalize(t)) => return Relation::Undefined,
+        (Value::Any, Value::Any) | (Value::Na, Value::Na) => return Relation::Equal,
+        (Value::Any, _) => return Relation::Superset,
+        (_, Value::Any) => return Relation::Subset,
+        (Value::Na, _) | (_, Value::Na) => return Relation::Disjoint,
+        (Value::Value(s), Value::Value(t)) => (normalize(s), normalize(t)),
+    };
+
+    if source == target {
+        return Relation::Equal;
+    }
+
+    compare_strings(&source, &target)
+}
+
+/// Match a source string, which may contain wildcards, against a target without wildcards.
+fn compare_strings(source: &[(char, bool)], target: &[(char, bool)]) -> Relation {
+    let is = |c: &(char, bool), w: char| *c == (w, false);
+
+    let mut start = 0;
+    let mut end = source.len();
+
+    // `None` means any number of characters, `Some(n)` up to `n` characters
+    let begins = if source.first().is_some_and(|c| is(c, '*')) {
+        start = 1;
+        None
+    } else {
+        while start < end && is(&source[start], '?') {
+            start += 1;
+        }
+        Some(start)
+    };
+
+    let ends = if end > start && is(&source[end - 1], '*') {
+        end -= 1;
+        None
+    } else {
+        let mut n = 0;
+        while end > start && is(&source[end - 1], '?') {
+            end -= 1;
+            n += 1;
+        }
+        Some(n)
+    };
+
+    let body = source[start..end]
+        .iter()
+        .map(|(c, _)| *c)
+        .collect::<Vec<_>>();
+    let target = target.iter().map(|(c, _)| *c).collect::<Vec<_>>();
+
+    if body.len() > target.len() {
+        return Relation::Disjoint;
+    }
+
+    for index in 0..=target.len() - body.len() {
+        if target[index..index + body.len()] != body[..] {
+            continue;
+        }
+        if let Some(begins) = begins {
+            if index > begins {
+                break;
+            }
+        }
+        let leftover = target.len() - index - body.len();
+        if let Some(ends) = ends {
+            if leftover > ends {
+                continue;
+            }
+        }
+        return Relation::Superset;
+    }
+
+    Relation::Disjoint
+}
+
+/// Compare all attributes of two names
+pub fn compare(source: &Wfn, target: &Wfn) -> [Relation; 11] {
+    let source = source.attributes();
+    let target = target.attributes();
+
+    std::array::from_fn(|i| compare_value(source[i].1, target[i].1))
+}
+
+/// Compare two names.
+///
+/// Names are disjoint if any attribute is, equal if all attributes are, and a superset (subset)
+/// if all attributes are either equal or a superset (subset). Every other combination is
+/// undefined.
+pub fn relation(source: &Wfn, target: &Wfn) -> Relation {
+    let attributes = compare(source, target);
+
+    if attributes.contains(&Relation::Disjoint) {
+        Relation::Disjoint
+    } else if attributes.contains(&Relation::Undefined) {
+        Relation::Undefined
+    } else if attributes.iter().all(|r| *r == Relation::Equal) {
+        Relation::Equal
+    } else if attributes
+        .iter()
+        .all(|r| matches!(r, Relation::Equal | Relation::Superset))
+    {
+        Relation::Superset
+    } else if attributes
+        .iter()
+        .all(|r| matches!(r, Relation::Equal | Relation::Subset))
+    {
+        Relation::Subset
+    } else {
+        Relation::Undefined
+    }
+}
+
+/// Check if the source name matches the target name, being equal to or a superset of it.
+pub fn is_match(source: &Wfn, target: &Wfn) -> bool {
+    matches!(
+        relation(source, target),
+        Relation::Equal | Relation::Superset
+    )
+}
+
+/// How to compare the `version` attribute
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum VersionMatch {
+    /// As defined by the specification
+    #[default]
+    Exact,
+    /// A source version also matches target versions it is a prefix of, so that `8` matches
+    /// `8.6`
+    Prefix,
+    /// The source version is ignored, the target version must be in the range
+    Range(Scheme, VersionRange),
+}
+
+impl VersionMatch {
+    fn compare(&self, source: &Value, target: &Value) -> Relation {
+        match (self, source, target) {
+            (Self::Exact, _, _) => compare_value(source, target),
+            (Self::Prefix, Value::Value(s), Value::Value(t)) => {
+                let (s, t) = (normalize(s), normalize(t));
+                if has_wildcards(&s) || has_wildcards(&t) {
+                    return compare_value(source, target);
+                }
+                let s = s.into_iter().map(|(c, _)| c).collect::<String>();
+                let t = t.into_iter().map(|(c, _)| c).collect::<String>();
+                match (s == t, version::is_prefix(&s, &t)) {
+                    (true, _) => Relation::Equal,
+                    (false, true) => Relation::Superset,
+                    (false, false) => Relation::Disjoint,
+                }
+            }
+            (Self::Prefix, _, _) => compare_value(source, target),
+            // a target without a concrete version (ANY or NA) can't be inside the range
+            (Self::Range(scheme, range), _, target) => match target.unquoted() {
+                Some(t) if range.contains(*scheme, &t) => Relation::Superset,
+                _ => Relation::Disjoint,
+            },
+        }
+    }
+}
+
+/// Like [`is_match`], but using a different strategy for comparing versions.
+pub fn is_match_with(source: &Wfn, target: &Wfn, versions: &VersionMatch) -> bool {
+    let mut attributes = compare(source, target);
+    attributes[3] = versions.compare(&source.version, &target.version);
+
+    attributes
+        .iter()
+        .all(|r| matches!(r, Relation::Equal | Relation::Superset))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn value(s: &str) -> Value {
+        Value::Value(s.to_string())
+    }
+
+    fn wfn(s: &str) -> Wfn {
+        Wfn::parse(s).unwrap_or_else(|err| panic!("{s}: {err:?}"))
+    }
+
+    /// NISTIR 7696, table 6-2: enumeration of attribute comparison set relations
+    #[test]
+    fn attribute_table() {
+        use Relation::*;
+
+        let i = value("i");
+        let k = value("k");
+        let wild = value("i*");
+
+        let table = [
+            (Value::Any, Value::Any, Equal),
+            (Value::Any, Value::Na, Superset),
+            (Value::Any, i.clone(), Superset),
+            (Value::Any, wild.clone(), Undefined),
+            (Value::Na, Value::Any, Subset),
+            (Value::Na, Value::Na, Equal),
+            (Value::Na, i.clone(), Disjoint),
+            (Value::Na, wild.clone(), Undefined),
+            (i.clone(), Value::Na, Disjoint),
+            (i.clone(), i.clone(), Equal),
+            (i.clone(), k.clone(), Disjoint),
+            (i.clone(), wild.clone(), Undefined),
+            (wild.clone(), value("ijk"), Superset),
+            (wild.clone(), Value::Any, Subset),
+            (wild.clone(), Value::Na, Disjoint),
+            (wild.clone(), k, Disjoint),
+            (wild.clone(), wild, Undefined),
+        ];
+
+        for (n, (source, target, expected)) in table.into_iter().enumerate() {
+            assert_eq!(
+                compare_value(&source, &target),
+                expected,
+                "row {}: {source} / {target}",
+                n + 1
+            );
+        }
+    }
+
+    #[test]
+    fn embedded_wildcards() {
+        use Relation::*;
+
+        for (source, target, expected) in [
+            (r"sp?", "sp2", Superset),
+            (r"sp??", "sp2", Superset),
+            (r"sp?", "sp23", Disjoint),
+            (r"?p2", "sp2", Superset),
+            (r"*2", "sp2", Superset),
+            (r"8\.*", r"8\.6", Superset),
+            (r"8\.*", r"9\.6", Disjoint),
+            (r"*\.6*", r"8\.6\.1", Superset),
+            (r"a\*b", r"a\*b", Equal),
+            (r"a\*b", "axb", Disjoint),
+            ("Reader", "reader", Equal),
+        ] {
+            assert_eq!(
+                compare_value(&value(source), &value(target)),
+                expected,
+                "{source} / {target}"
+            );
+        }
+    }
+
+    /// NISTIR 7696, section 6.2, example 1: the target contains wildcards
+    #[test]
+    fn example_undefined() {
+        let source = wfn("cpe:2.3:a:microsoft:internet_explorer:8.0.6001:beta:*:*:*:*:*:*");
+        let target = wfn("cpe:2.3:a:microsoft:internet_explorer:8.*:sp?:*:*:*:*:*:*");
+
+        assert_eq!(relation(&source, &target), Relation::Undefined);
+    }
+
+    /// NISTIR 7696, section 6.2, example 2
+    #[test]
+    fn example_superset() {
+        let source = wfn("cpe:2.3:a:adobe:*:9.*:*:*:*:*:*:*:*");
+        let target = wfn("cpe:2.3:a:adobe:reader:9.3.2:-:-:*:*:*:*:*");
+
+        assert_eq!(relation(&source, &target), Relation::Superset);
+        assert!(is_match(&source, &target));
+    }
+
+    /// NISTIR 7696, section 6.2, example 3
+    #[test]
+    fn example_subset() {
+        let source = wfn("cpe:2.3:a:adobe:reader:9.3.2:-:-:*:*:*:*:*");
+        let target = wfn("cpe:2.3:a:adobe:*:*:*:*:*:*:*:*:*");
+
+        assert_eq!(relation(&source, &target), Relation::Subset);
+        assert!(!is_match(&source, &target));
+    }
+
+    #[test]
+    fn disjoint() {
+        let source = wfn("cpe:2.3:a:adobe:reader:9.3.2:*:*:*:*:*:*:*");
+        let target = wfn("cpe:2.3:a:microsoft:reader:9.3.2:*:*:*:*:*:*:*");
+
+        assert_eq!(relation(&source, &target), Relation::Disjoint);
+    }
+
+    #[test]
+    fn bindings_are_equivalent() {
+        let uri = wfn("cpe:/a:redhat:enterprise_linux:8::appstream");
+        let fs = wfn("cpe:2.3:a:redhat:enterprise_linux:8:*:appstream:*:*:*:*:*");
+
+        assert_eq!(relation(&uri, &fs), Relation::Equal);
+
+        let vex = wfn("cpe:/a:redhat:enterprise_linux:8");
+        assert_eq!(relation(&vex, &uri), Relation::Superset);
+    }
+
+    #[test]
+    fn version_prefix() {
+        let vex = wfn("cpe:/a:redhat:enterprise_linux:8");
+        let sbom = wfn("cpe:/a:redhat:enterprise_linux:8.6");
+
+        assert!(!is_match(&vex, &sbom));
+        assert!(is_match_with(&vex, &sbom, &VersionMatch::Prefix));
+        assert!(!is_match_with(
+            &vex,
+            &wfn("cpe:/a:redhat:enterprise_linux:80"),
+            &VersionMatch::Prefix
+        ));
+    }
+
+    #[test]
+    fn version_range() {
+        let range = VersionMatch::Range(
+            Scheme::Rpm,
+            VersionRange::from_nvd(None, None, None, Some("1.1.1k-7.el8_6")),
+        );
+        let nvd = wfn("cpe:2.3:a:openssl:openssl:*:*:*:*:*:*:*:*");
+
+        assert!(is_match_with(
+            &nvd,
+            &wfn("cpe:2.3:a:openssl:openssl:1.1.1k-5.el8_5:*:*:*:*:*:*:*"),
+            &range
+        ));
+        assert!(!is_match_with(
+            &nvd,
+            &wfn("cpe:2.3:a:openssl:openssl:1.1.1k-7.el8_6:*:*:*:*:*:*:*"),
+            &range
+        ));
+        assert!(!is_match_with(
+            &nvd,
+            &wfn("cpe:2.3:a:openssl:openssl:*:*:*:*:*:*:*:*"),
+            &range
+        ));
+        assert!(!is_match_with(
+            &nvd,
+            &wfn("cpe:2.3:a:openssl:openssl:-:*:*:*:*:*:*:*"),
+            &range
+        ));
+    }
+
+    #[test]
+    fn uri_wildcards() {
+        let source = wfn("cpe:/a:redhat:enterprise_linux:8%02");
+        let target = wfn("cpe:/a:redhat:enterprise_linux:8.6");
+
+        assert!(is_match(&source, &target));
+    }
+}
@@ -0,0 +1,573 @@
+//! Parsing and validation of CPE names, following the CPE 2.3 naming specification (NISTIR 7695).
+//!
+//! Both bindings (the 2.2 style URI and the 2.3 formatted string) are parsed into a well-formed
+//! name ([`Wfn`]). Attribute values are kept in WFN form: all characters other than letters,
+//! digits and `_` are quoted with a backslash, while unquoted `*` and `?` are wildcards.
+
+use std::fmt::{Display, Formatter};
+
+pub const ATTRIBUTES: [&str; 11] = [
+    "part",
+    "vendor",
+    "product",
+    "version",
+    "update",
+    "edition",
+    "language",
+    "sw_edition",
+    "target_sw",
+    "target_hw",
+    "other",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Binding {
+    /// `cpe:/…`, the CPE 2.2 URI
+    Uri,
+    /// `cpe:2.3:…`
+    FormattedString,
+}
+
+impl Binding {
+    pub fn detect(cpe: &str) -> Option<Self> {
+        if cpe.starts_with("cpe:2.3:") {
+            Some(Self::FormattedString)
+        } else if cpe.starts_with("cpe:/") {
+            Some(Self::Uri)
+        } else {
+            None
+        }
+    }
+
+    /// The binding declared by an SPDX external reference type
+    pub fn from_reference_type(reference_type: &str) -> Option<Self> {
+        match reference_type {
+            "cpe22Type" => Some(Self::Uri),
+            "cpe23Type" => Some(Self::FormattedString),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Value {
+    #[default]
+    Any,
+    /// Not applicable
+    Na,
+    /// A value in WFN form
+    Value(String),
+}
+
+impl Value {
+    /// The characters of the value, with a flag telling if they were quoted
+    pub fn chars(value: &str) -> Vec<(char, bool)> {
+        let mut result = vec![];
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some(c) = chars.next() {
+                        result.push((c, true));
+                    }
+                }
+                c => result.push((c, false)),
+            }
+        }
+        result
+    }
+
+    /// The value with all quoting removed, `None` for ANY and NA
+    pub fn unquoted(&self) -> Option<String> {
+        match self {
+            Self::Value(value) => Some(Self::chars(value).into_iter().map(|(c, _)| c).collect()),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => f.write_str("ANY"),
+            Self::Na => f.write_str("NA"),
+            Self::Value(value) => write!(f, "\"{value}\""),
+        }
+    }
+}
+
+/// A well-formed CPE name
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Wfn {
+    pub part: Value,
+    pub vendor: Value,
+    pub product: Value,
+    pub version: Value,
+    pub update: Value,
+    pub edition: Value,
+    pub language: Value,
+    pub sw_edition: Value,
+    pub target_sw: Value,
+    pub target_hw: Value,
+    pub other: Value,
+}
+
+impl Wfn {
+    pub fn attributes(&self) -> [(&'static str, &Value); 11] {
+        [
+            (ATTRIBUTES[0], &self.part),
+            (ATTRIBUTES[1], &self.vendor),
+            (ATTRIBUTES[2], &self.product),
+            (ATTRIBUTES[3], &self.version),
+            (ATTRIBUTES[4], &self.update),
+            (ATTRIBUTES[5], &self.edition),
+            (ATTRIBUTES[6], &self.language),
+            (ATTRIBUTES[7], &self.sw_edition),
+            (ATTRIBUTES[8], &self.target_sw),
+            (ATTRIBUTES[9], &self.target_hw),
+            (ATTRIBUTES[10], &self.other),
+        ]
+    }
+
+    fn attribute_mut(&mut self, index: usize) -> &mut Value {
+        match index {
+            0 => &mut self.part,
+            1 => &mut self.vendor,
+            2 => &mut self.product,
+            3 => &mut self.version,
+            4 => &mut self.update,
+            5 => &mut self.edition,
+            6 => &mut self.language,
+            7 => &mut self.sw_edition,
+            8 => &mut self.target_sw,
+            9 => &mut self.target_hw,
+            _ => &mut self.other,
+        }
+    }
+
+    /// Parse a CPE name in either binding
+    pub fn parse(cpe: &str) -> Result<Self, Vec<Violation>> {
+        let mut violations = vec![];
+
+        let wfn = if let Some(rest) = cpe.strip_prefix("cpe:2.3:") {
+            parse_formatted_string(rest, &mut violations)
+        } else if let Some(rest) = cpe.strip_prefix("cpe:/") {
+            parse_uri(rest, &mut violations)
+        } else {
+            return Err(vec![Violation::UnknownBinding]);
+        };
+
+        if violations.is_empty() {
+            Ok(wfn)
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+impl Display for Wfn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("wfn:[")?;
+        for (i, (name, value)) in self.attributes().into_iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{name}={value}")?;
+        }
+        f.write_str("]")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Violation {
+    /// Neither `cpe:/` nor `cpe:2.3:`
+    UnknownBinding,
+    /// The binding doesn't match the declared type
+    BindingMismatch {
+        declared: Binding,
+        actual: Binding,
+    },
+    /// Wrong number of components
+    ComponentCount(usize),
+    /// The part is not one of `a`, `o` or `h`
+    InvalidPart(String),
+    InvalidCharacter {
+        attribute: &'static str,
+        character: char,
+    },
+    /// A broken percent encoding or backslash quoting
+    InvalidEscape {
+        attribute: &'static str,
+        sequence: String,
+    },
+    /// Formatted strings must use `*` for ANY, rather than an empty component
+    EmptyComponent {
+        attribute: &'static str,
+    },
+    /// Wildcards in places where they are not allowed
+    InvalidWildcard {
+        attribute: &'static str,
+    },
+    /// A URI edition starting with `~`, but not packing five attributes
+    InvalidPacking,
+    InvalidLanguage(String),
+}
+
+impl Violation {
+    /// A short name for the kind of violation, suitable for aggregating
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnknownBinding => "unknown-binding",
+            Self::BindingMismatch { .. } => "binding-mismatch",
+            Self::ComponentCount(_) => "component-count",
+            Self::InvalidPart(_) => "invalid-part",
+            Self::InvalidCharacter { .. } => "invalid-character",
+            Self::InvalidEscape { .. } => "invalid-escape",
+            Self::EmptyComponent { .. } => "empty-component",
+            Self::InvalidWildcard { .. } => "invalid-wildcard",
+            Self::InvalidPacking => "invalid-packing",
+            Self::InvalidLanguage(_) => "invalid-language",
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownBinding => write!(f, "not a CPE URI or formatted string"),
+            Self::BindingMismatch { declared, actual } => {
+                write!(f, "declared as {declared:?}, but is {actual:?}")
+            }
+            Self::ComponentCount(n) => write!(f, "invalid number of components: {n}"),
+            Self::InvalidPart(part) => write!(f, "invalid part: {part}"),
+            Self::InvalidCharacter {
+                attribute,
+                character,
+            } => write!(f, "invalid character in {attribute}: {character:?}"),
+            Self::InvalidEscape {
+                attribute,
+                sequence,
+            } => write!(f, "invalid escape sequence in {attribute}: {sequence}"),
+            Self::EmptyComponent { attribute } => write!(f, "empty component: {attribute}"),
+            Self::InvalidWildcard { attribute } => write!(f, "misplaced wildcard in {attribute}"),
+            Self::InvalidPacking => write!(f, "invalid packed edition"),
+            Self::InvalidLanguage(lang) => write!(f, "invalid language: {lang}"),
+        }
+    }
+}
+
+/// Validate a CPE name, and check it against the binding it was declared as
+pub fn validate(cpe: &str, declared: Option<Binding>) -> Vec<Violation> {
+    let mut violations = match Wfn::parse(cpe) {
+        Ok(_) => vec![],
+        Err(violations) => violations,
+    };
+
+    if let (Some(declared), Some(actual)) = (declared, Binding::detect(cpe)) {
+        if declared != actual {
+            violations.push(Violation::BindingMismatch { declared, actual });
+        }
+    }
+
+    violations
+}
+
+fn parse_uri(rest: &str, violations: &mut Vec<Violation>) -> Wfn {
+    let mut wfn = Wfn::default();
+
+    let components = rest.split(':').collect::<Vec<_>>();
+    if components.len() > 7 {
+        violations.push(Violation::ComponentCount(components.len()));
+    }
+
+    for (i, component) in components.into_iter().enumerate().take(7) {
+        if i == 5 && component.starts_with('~') {
+            // packed edition: ~edition~sw_edition~target_sw~target_hw~other
+            let packed = component.split('~').skip(1).collect::<Vec<_>>();
+            if packed.len() != 5 {
+                violations.push(Violation::InvalidPacking);
+            }
+            for (j, component) in packed.into_iter().enumerate().take(5) {
+                let index = if j == 0 { 5 } else { j + 6 };
+                *wfn.attribute_mut(index) = decode_uri(ATTRIBUTES[index], component, violations);
+            }
+            continue;
+        }
+
+        *wfn.attribute_mut(i) = decode_uri(ATTRIBUTES[i], component, violations);
+    }
+
+    check(&wfn, violations);
+
+    wfn
+}
+
+fn decode_uri(attribute: &'static str, value: &str, violations: &mut Vec<Violation>) -> Value {
+    match value {
+        "" => return Value::Any,
+        "-" => return Value::Na,
+        _ => {}
+    }
+
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_ascii_alphanumeric() || c == '_' => result.push(c),
+            // `~` only packs the edition at its start, anywhere else it's a regular character
+            '-' | '.' | '~' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '%' => {
+                let sequence = chars.by_ref().take(2).collect::<String>();
+                match u8::from_str_radix(&sequence, 16) {
+                    Ok(0x01) if sequence.len() == 2 => result.push('?'),
+                    Ok(0x02) if sequence.len() == 2 => result.push('*'),
+                    Ok(b)
+                        if sequence.len() == 2
+                            && b.is_ascii_punctuation()
+                            && !matches!(b, b'-' | b'.' | b'_') =>
+                    {
+                        result.push('\\');
+                        result.push(b as char);
+                    }
+                    _ => violations.push(Violation::InvalidEscape {
+                        attribute,
+                        sequence: format!("%{sequence}"),
+                    }),
+                }
+            }
+            character => violations.push(Violation::InvalidCharacter {
+                attribute,
+                character,
+            }),
+        }
+    }
+
+    Value::Value(result)
+}
+
+fn parse_formatted_string(rest: &str, violations: &mut Vec<Violation>) -> Wfn {
+    let mut wfn = Wfn::default();
+
+    // split on unquoted colons
+    let mut components = vec![];
+    let mut current = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                current.extend(chars.next());
+            }
+            ':' => components.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    components.push(current);
+
+    if components.len() != 11 {
+        violations.push(Violation::ComponentCount(components.len()));
+    }
+
+    for (i, component) in components.iter().enumerate().take(11) {
+        *wfn.attribute_mut(i) = decode_formatted(ATTRIBUTES[i], component, violations);
+    }
+
+    check(&wfn, violations);
+
+    wfn
+}
+
+fn decode_formatted(
+    attribute: &'static str,
+    value: &str,
+    violations: &mut Vec<Violation>,
+) -> Value {
+    match value {
+        "*" => return Value::Any,
+        "-" => return Value::Na,
+        "" => violations.push(Violation::EmptyComponent { attribute }),
+        _ => {}
+    }
+
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) if c.is_ascii_alphanumeric() || c == '_' => result.push(c),
+                Some(c) if c.is_ascii_punctuation() => {
+                    result.push('\\');
+                    result.push(c);
+                }
+                Some(c) => violations.push(Violation::InvalidEscape {
+                    attribute,
+                    sequence: format!("\\{c}"),
+                }),
+                None => violations.push(Violation::InvalidEscape {
+                    attribute,
+                    sequence: "\\".to_string(),
+                }),
+            },
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '*' || c == '?' => result.push(c),
+            '-' | '.' => {
+                result.push('\\');
+                result.push(c);
+            }
+            character => violations.push(Violation::InvalidCharacter {
+                attribute,
+                character,
+            }),
+        }
+    }
+
+    Value::Value(result)
+}
+
+/// checks on the parsed name, independent of the binding
+fn check(wfn: &Wfn, violations: &mut Vec<Violation>) {
+    match &wfn.part {
+        Value::Any => {}
+        Value::Value(part) if matches!(part.as_str(), "a" | "o" | "h") => {}
+        part => violations.push(Violation::InvalidPart(part.to_string())),
+    }
+
+    if let Some(lang) = wfn.language.unquoted() {
+        if !valid_language(&lang) {
+            violations.push(Violation::InvalidLanguage(lang));
+        }
+    }
+
+    for (attribute, value) in wfn.attributes() {
+        if let Value::Value(value) = value {
+            if !valid_wildcards(value) {
+                violations.push(Violation::InvalidWildcard { attribute });
+            }
+        }
+    }
+}
+
+/// `language` must be an RFC 5646 tag, limited to a language and an optional region
+fn valid_language(lang: &str) -> bool {
+    let mut s = lang.split('-');
+    let language = s.next().unwrap_or_default();
+    let region = s.next();
+
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && match region {
+            None => true,
+            Some(r) => {
+                (r.len() == 2 && r.chars().all(|c| c.is_ascii_alphabetic()))
+                    || (r.len() == 3 && r.chars().all(|c| c.is_ascii_digit()))
+            }
+        }
+        && s.next().is_none()
+}
+
+/// A single `*`, or a run of `?`, may only appear at the beginning and/or the end of a value
+fn valid_wildcards(value: &str) -> bool {
+    let chars = Value::chars(value);
+    let wildcard = |(c, quoted): &(char, bool)| !quoted && (*c == '*' || *c == '?');
+
+    let leading = chars.iter().take_while(|c| wildcard(c)).count();
+    if leading == chars.len() {
+        return valid_run(&chars);
+    }
+    let trailing = chars.iter().rev().take_while(|c| wildcard(c)).count();
+
+    valid_run(&chars[..leading])
+        && valid_run(&chars[chars.len() - trailing..])
+        && !chars[leading..chars.len() - trailing].iter().any(wildcard)
+}
+
+fn valid_run(run: &[(char, bool)]) -> bool {
+    run.iter().all(|(c, _)| *c == '?') || run == [('*', false)]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn value(v: &str) -> Value {
+        Value::Value(v.to_string())
+    }
+
+    fn kinds(cpe: &str) -> Vec<&'static str> {
+        validate(cpe, None).iter().map(Violation::kind).collect()
+    }
+
+    #[test]
+    fn test_parse_uri() {
+        let wfn = Wfn::parse("cpe:/a:redhat:openssl:1.1.1k::~~~rhel~x86_64~").unwrap();
+        assert_eq!(wfn.part, value("a"));
+        assert_eq!(wfn.product, value("openssl"));
+        assert_eq!(wfn.version, value("1\\.1\\.1k"));
+        assert_eq!(wfn.update, Value::Any);
+        assert_eq!(wfn.edition, Value::Any);
+        assert_eq!(wfn.target_sw, value("rhel"));
+        assert_eq!(wfn.target_hw, value("x86_64"));
+
+        let wfn = Wfn::parse("cpe:/a:vendor:product:1.0~beta:-:%02").unwrap();
+        assert_eq!(wfn.version, value("1\\.0\\~beta"));
+        assert_eq!(wfn.update, Value::Na);
+        assert_eq!(wfn.edition, value("*"));
+    }
+
+    #[test]
+    fn test_parse_formatted_string() {
+        let wfn = Wfn::parse("cpe:2.3:o:redhat:enterprise_linux:8\\.6:*:*:en-us:*:*:*:-").unwrap();
+        assert_eq!(wfn.part, value("o"));
+        assert_eq!(wfn.version, value("8\\.6"));
+        assert_eq!(wfn.language, value("en\\-us"));
+        assert_eq!(wfn.other, Value::Na);
+
+        // unquoted dots are accepted, and quoted like in the URI binding
+        let wfn = Wfn::parse("cpe:2.3:a:redhat:openssl:1.1.1k:*:*:*:*:*:*:*").unwrap();
+        assert_eq!(wfn.version, value("1\\.1\\.1k"));
+    }
+
+    #[test]
+    fn test_violations() {
+        assert_eq!(kinds("cpe:/a:redhat:openssl"), Vec::<&str>::new());
+        assert_eq!(kinds("cpe:2.3:a:redhat:openssl"), ["component-count"]);
+        assert_eq!(kinds("pkg:rpm/redhat/openssl"), ["unknown-binding"]);
+        assert_eq!(kinds("cpe:/x:redhat:openssl"), ["invalid-part"]);
+        assert_eq!(kinds("cpe:/a:red hat:openssl"), ["invalid-character"]);
+        assert_eq!(kinds("cpe:/a:redhat:openssl%zz"), ["invalid-escape"]);
+        assert_eq!(
+            kinds("cpe:2.3:a:redhat::*:*:*:*:*:*:*:*"),
+            ["empty-component"]
+        );
+        assert_eq!(
+            kinds("cpe:2.3:a:redhat:open*ssl:*:*:*:*:*:*:*:*"),
+            ["invalid-wildcard"]
+        );
+        assert_eq!(kinds("cpe:/a:redhat:openssl:1:2:~a~b"), ["invalid-packing"]);
+        assert_eq!(
+            kinds("cpe:/a:redhat:openssl:1:2:3:english"),
+            ["invalid-language"]
+        );
+
+        assert_eq!(
+            validate("cpe:/a:redhat:openssl", Some(Binding::FormattedString)),
+            [Violation::BindingMismatch {
+                declared: Binding::FormattedString,
+                actual: Binding::Uri,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(valid_wildcards("*openssl"));
+        assert!(valid_wildcards("openssl??"));
+        assert!(valid_wildcards("??openssl*"));
+        assert!(valid_wildcards("open\\*ssl"));
+        assert!(!valid_wildcards("**openssl"));
+        assert!(!valid_wildcards("*?openssl"));
+        assert!(!valid_wildcards("open?ssl"));
+    }
+}
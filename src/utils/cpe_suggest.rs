@@ -0,0 +1,264 @@
+use crate::utils::cpe_dictionary::{CpeItem, CpeList};
+use std::collections::BTreeMap;
+
+/// Why a dictionary entry was suggested
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reason {
+    /// Same part, vendor and product, differing only in the later components (version, update, …)
+    VersionStripped,
+    /// Same vendor, with a similarly spelled product
+    ProductSpelling,
+    /// Similarly spelled vendor
+    VendorSpelling,
+}
+
+#[derive(Clone, Debug)]
+pub struct Suggestion<'a> {
+    pub item: &'a CpeItem,
+    pub reason: Reason,
+    /// Sum of the edit distances of all components
+    pub distance: usize,
+}
+
+/// an item, with its name split into components
+type Entry<'a> = (&'a CpeItem, Vec<&'a str>);
+
+/// Proposes dictionary entries for CPEs which are not part of the dictionary.
+pub struct Suggester<'a> {
+    /// items, grouped by part and vendor
+    by_vendor: BTreeMap<(&'a str, &'a str), Vec<Entry<'a>>>,
+}
+
+/// split a 2.2 URI or a 2.3 formatted string into its components, starting with the part.
+///
+/// Trailing ANY components are dropped, and `*` is treated like an empty 2.2 component, so both
+/// bindings of the same name compare equal.
+fn components(cpe: &str) -> Vec<&str> {
+    let rest = if let Some(rest) = cpe.strip_prefix("cpe:2.3:") {
+        rest
+    } else {
+        cpe.strip_prefix("cpe:/").unwrap_or(cpe)
+    };
+
+    let mut result = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ':' => {
+                result.push(&rest[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    result.push(&rest[start..]);
+
+    for c in &mut result {
+        if *c == "*" {
+            *c = "";
+        }
+    }
+    while result.len() > 1 && result.last() == Some(&"") {
+        result.pop();
+    }
+
+    result
+}
+
+/// Levenshtein distance, on characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(cur).min(row[j])
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// the edit distance we still consider a spelling variant
+fn close(a: &str, b: &str) -> Option<usize> {
+    let max = (a.chars().count().max(b.chars().count()) / 4).max(2);
+    let d = edit_distance(a, b);
+    (d <= max).then_some(d)
+}
+
+fn component_distance(a: &[&str], b: &[&str]) -> usize {
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            edit_distance(
+                a.get(i).copied().unwrap_or_default(),
+                b.get(i).copied().unwrap_or_default(),
+            )
+        })
+        .sum()
+}
+
+impl<'a> Suggester<'a> {
+    pub fn new(list: &'a CpeList) -> Self {
+        let mut by_vendor: BTreeMap<_, Vec<_>> = BTreeMap::new();
+
+        for item in &list.items {
+            let c = components(&item.name);
+            if let [part, vendor, ..] = *c.as_slice() {
+                by_vendor.entry((part, vendor)).or_default().push((item, c));
+            }
+        }
+
+        Self { by_vendor }
+    }
+
+    /// Suggest up to `limit` entries, best first.
+    pub fn suggest(&self, cpe: &str, limit: usize) -> Vec<Suggestion<'a>> {
+        let c = components(cpe);
+        let [part, vendor, product, ..] = c.as_slice() else {
+            return vec![];
+        };
+
+        let mut result = vec![];
+
+        if let Some(items) = self.by_vendor.get(&(*part, *vendor)) {
+            for (item, ic) in items {
+                let ip = ic.get(2).copied().unwrap_or_default();
+                let reason = if ip == *product {
+                    Reason::VersionStripped
+                } else if close(product, ip).is_some() {
+                    Reason::ProductSpelling
+                } else {
+                    continue;
+                };
+                result.push(Suggestion {
+                    item,
+                    reason,
+                    distance: component_distance(&c, ic),
+                });
+            }
+        }
+
+        if result.is_empty() {
+            for ((p, v), items) in &self.by_vendor {
+                if p != part || v == vendor || close(vendor, v).is_none() {
+                    continue;
+                }
+                for (item, ic) in items {
+                    if close(product, ic.get(2).copied().unwrap_or_default()).is_some() {
+                        result.push(Suggestion {
+                            item,
+                            reason: Reason::VendorSpelling,
+                            distance: component_distance(&c, ic),
+                        });
+                    }
+                }
+            }
+        }
+
+        result.sort_by(|a, b| {
+            (a.reason, a.distance, &a.item.name).cmp(&(b.reason, b.distance, &b.item.name))
+        });
+        result.truncate(limit);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::cpe_dictionary::{Generator, Title};
+
+    fn list(names: &[&str]) -> CpeList {
+        CpeList {
+            generator: Generator::default(),
+            items: names
+                .iter()
+                .map(|name| CpeItem {
+                    name: name.to_string(),
+                    title: vec![Title {
+                        lang: "en-US".to_string(),
+                        title: name.to_string(),
+                    }],
+                })
+                .collect(),
+        }
+    }
+
+    fn suggest<'a>(list: &'a CpeList, cpe: &str) -> Vec<(&'a str, Reason)> {
+        Suggester::new(list)
+            .suggest(cpe, 5)
+            .into_iter()
+            .map(|s| (s.item.name.as_str(), s.reason))
+            .collect()
+    }
+
+    #[test]
+    fn test_components() {
+        assert_eq!(
+            components("cpe:/a:redhat:openssl:1.1.1k"),
+            ["a", "redhat", "openssl", "1.1.1k"]
+        );
+        assert_eq!(
+            components("cpe:2.3:a:redhat:openssl:1.1.1k:*:*:*:*:*:*:*"),
+            ["a", "redhat", "openssl", "1.1.1k"]
+        );
+        assert_eq!(
+            components("cpe:2.3:a:redhat:open\\:ssl:*:*:*:*:*:*:*:*"),
+            ["a", "redhat", "open\\:ssl"]
+        );
+        assert_eq!(
+            components("cpe:/o:redhat:enterprise_linux:8::"),
+            ["o", "redhat", "enterprise_linux", "8"]
+        );
+    }
+
+    #[test]
+    fn test_close() {
+        assert_eq!(close("openssl", "opensll"), Some(1));
+        assert_eq!(close("openssl", "zlib"), None);
+        // characters, not bytes: 8 characters allow a distance of 2, not 16 bytes a distance of 4
+        assert_eq!(close("ääääääää", "ääääöööö"), None);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let list = list(&[
+            "cpe:/a:redhat:openssl:1.1.1k",
+            "cpe:/a:redhat:openssl",
+            "cpe:/a:redhat:opensssl",
+            "cpe:/a:red_hat:openssl",
+            "cpe:/a:gnu:zlib",
+        ]);
+
+        assert_eq!(
+            suggest(&list, "cpe:2.3:a:redhat:openssl:1.1.1:*:*:*:*:*:*:*"),
+            [
+                ("cpe:/a:redhat:openssl:1.1.1k", Reason::VersionStripped),
+                ("cpe:/a:redhat:openssl", Reason::VersionStripped),
+                ("cpe:/a:redhat:opensssl", Reason::ProductSpelling),
+            ]
+        );
+        assert_eq!(
+            suggest(&list, "cpe:/a:redhaat:openssl"),
+            [
+                ("cpe:/a:redhat:openssl", Reason::VendorSpelling),
+                ("cpe:/a:red_hat:openssl", Reason::VendorSpelling),
+                ("cpe:/a:redhat:opensssl", Reason::VendorSpelling),
+                ("cpe:/a:redhat:openssl:1.1.1k", Reason::VendorSpelling),
+            ]
+        );
+        assert_eq!(suggest(&list, "cpe:/a:apache:httpd"), []);
+    }
+}
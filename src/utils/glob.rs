@@ -0,0 +1,54 @@
+//! Simple glob patterns, for filters and queries.
+
+/// match a value against a pattern, where `*` matches any sequence and `?` any single character
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let value = value.as_bytes();
+
+    let (mut p, mut v) = (0, 0);
+    // the position after the last `*`, and the value position it was tried at
+    let mut backtrack = None;
+
+    while v < value.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                p += 1;
+                backtrack = Some((p, v));
+            }
+            Some(c) if *c == b'?' || c.eq_ignore_ascii_case(&value[v]) => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bv)) => {
+                    p = bp;
+                    v = bv + 1;
+                    backtrack = Some((bp, bv + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("CVE-2023-*", "CVE-2023-0286"));
+        assert!(glob_match("cve-2023-*", "CVE-2023-0286"));
+        assert!(glob_match("CVE-2023-028?", "CVE-2023-0286"));
+        assert!(glob_match("*-0286", "CVE-2023-0286"));
+        assert!(glob_match("CVE-*-02*6", "CVE-2023-0286"));
+        assert!(glob_match("*", "CVE-2023-0286"));
+        assert!(glob_match("CVE-2023-0286", "CVE-2023-0286"));
+
+        assert!(!glob_match("CVE-2022-*", "CVE-2023-0286"));
+        assert!(!glob_match("CVE-2023-028", "CVE-2023-0286"));
+        assert!(!glob_match("CVE-2023-0286?", "CVE-2023-0286"));
+    }
+}
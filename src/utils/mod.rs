@@ -1,2 +1,10 @@
+pub mod correlate;
 pub mod cpe_dictionary;
+pub mod cpe_match;
+pub mod cpe_name;
+pub mod cpe_suggest;
+pub mod glob;
+pub mod purl;
+pub mod query_lang;
+pub mod version;
 pub mod vex;
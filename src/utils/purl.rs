@@ -0,0 +1,335 @@
+//! Package URLs, following the purl specification.
+
+use crate::utils::version::{Scheme, VersionRange};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Write};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Purl {
+    pub ty: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub version: Option<String>,
+    pub qualifiers: BTreeMap<String, String>,
+    pub subpath: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PurlError {
+    MissingScheme,
+    MissingType,
+    MissingName,
+    InvalidType(String),
+    InvalidEncoding(String),
+}
+
+impl Display for PurlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingScheme => write!(f, "missing 'pkg:' scheme"),
+            Self::MissingType => write!(f, "missing type"),
+            Self::MissingName => write!(f, "missing name"),
+            Self::InvalidType(ty) => write!(f, "invalid type: {ty}"),
+            Self::InvalidEncoding(s) => write!(f, "invalid percent encoding: {s}"),
+        }
+    }
+}
+
+impl std::error::Error for PurlError {}
+
+fn decode(s: &str) -> Result<String, PurlError> {
+    let bytes = s.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let b = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                    .ok_or_else(|| PurlError::InvalidEncoding(s.to_string()))?;
+                result.push(b);
+                i += 3;
+            }
+            b => {
+                result.push(b);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(result).map_err(|_| PurlError::InvalidEncoding(s.to_string()))
+}
+
+fn encode(s: &str, keep: &str, f: &mut Formatter<'_>) -> std::fmt::Result {
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) || keep.as_bytes().contains(&b) {
+            f.write_char(b as char)?;
+        } else {
+            write!(f, "%{b:02X}")?;
+        }
+    }
+    Ok(())
+}
+
+impl Purl {
+    /// Parse and normalize a package URL
+    pub fn parse(purl: &str) -> Result<Self, PurlError> {
+        let rest = purl
+            .trim()
+            .strip_prefix("pkg:")
+            .ok_or(PurlError::MissingScheme)?
+            .trim_start_matches('/');
+
+        let (rest, subpath) = match rest.split_once('#') {
+            Some((rest, subpath)) => {
+                let subpath = subpath
+                    .split('/')
+                    .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+                    .map(decode)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join("/");
+                (rest, Some(subpath).filter(|s| !s.is_empty()))
+            }
+            None => (rest, None),
+        };
+
+        let (rest, qualifiers) = match rest.split_once('?') {
+            Some((rest, qualifiers)) => {
+                let mut result = BTreeMap::new();
+                for pair in qualifiers.split('&') {
+                    if let Some((key, value)) = pair.split_once('=') {
+                        let value = decode(value)?;
+                        if !value.is_empty() {
+                            result.insert(key.to_ascii_lowercase(), value);
+                        }
+                    }
+                }
+                (rest, result)
+            }
+            None => (rest, BTreeMap::new()),
+        };
+
+        let rest = rest.trim_end_matches('/');
+        // the version separator must come after the last '/', an npm scope (`@angular`) is not one
+        let name_start = rest.rfind('/').map_or(0, |i| i + 1);
+        let (rest, version) = match rest[name_start..].rfind('@') {
+            Some(i) => {
+                let (rest, version) = rest.split_at(name_start + i);
+                (rest, Some(decode(&version[1..])?))
+            }
+            None => (rest, None),
+        };
+
+        let (ty, rest) = rest.split_once('/').ok_or(PurlError::MissingType)?;
+        let ty = ty.to_ascii_lowercase();
+        if ty.is_empty()
+            || !ty
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-'))
+        {
+            return Err(PurlError::InvalidType(ty));
+        }
+
+        let mut segments = rest
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(decode)
+            .collect::<Result<Vec<_>, _>>()?;
+        let name = segments.pop().ok_or(PurlError::MissingName)?;
+        let namespace = Some(segments.join("/")).filter(|s| !s.is_empty());
+
+        let mut result = Self {
+            ty,
+            namespace,
+            name,
+            version,
+            qualifiers,
+            subpath,
+        };
+        result.normalize();
+
+        Ok(result)
+    }
+
+    /// apply the type specific normalization rules
+    fn normalize(&mut self) {
+        match self.ty.as_str() {
+            "apk" | "bitbucket" | "composer" | "deb" | "github" | "golang" | "hex" | "npm" => {
+                self.namespace = self.namespace.as_ref().map(|n| n.to_lowercase());
+                self.name = self.name.to_lowercase();
+            }
+            "pypi" => {
+                self.name = self.name.to_lowercase().replace('_', "-");
+            }
+            _ => {}
+        }
+    }
+
+    /// The version scheme to use for comparing versions of this type
+    pub fn scheme(&self) -> Scheme {
+        match self.ty.as_str() {
+            "rpm" => Scheme::Rpm,
+            "npm" | "cargo" | "golang" => Scheme::Semver,
+            _ => self
+                .version
+                .as_deref()
+                .map(Scheme::detect)
+                .unwrap_or(Scheme::Rpm),
+        }
+    }
+
+    fn same_package(&self, other: &Self) -> bool {
+        self.ty == other.ty && self.namespace == other.namespace && self.name == other.name
+    }
+
+    /// Check if this purl (e.g. from a VEX document) matches another one (e.g. from an SBOM).
+    ///
+    /// The version, subpath and qualifiers are only compared if they are set on this purl.
+    /// Qualifiers must be a subset of the other's qualifiers.
+    pub fn matches(&self, other: &Self) -> bool {
+        self.same_package(other)
+            && match (&self.version, &other.version) {
+                (None, _) => true,
+                (Some(a), Some(b)) => self.scheme().compare(a, b).is_eq(),
+                (Some(_), None) => false,
+            }
+            && (self.subpath.is_none() || self.subpath == other.subpath)
+            && self
+                .qualifiers
+                .iter()
+                .all(|(k, v)| other.qualifiers.get(k) == Some(v))
+    }
+
+    /// The version to compare, which for RPMs includes the `epoch` qualifier, like `1:1.1.1k-7`
+    pub fn comparable_version(&self) -> Option<String> {
+        let version = self.version.as_deref()?;
+        match self.qualifiers.get("epoch") {
+            Some(epoch) if self.scheme() == Scheme::Rpm && !version.contains(':') => {
+                Some(format!("{epoch}:{version}"))
+            }
+            _ => Some(version.to_string()),
+        }
+    }
+
+    /// Check if the other purl is the same package, with a version in the range
+    pub fn matches_range(&self, range: &VersionRange, other: &Self) -> bool {
+        self.same_package(other)
+            && other
+                .comparable_version()
+                .is_some_and(|version| range.contains(self.scheme(), &version))
+    }
+}
+
+impl Display for Purl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "pkg:{}/", self.ty)?;
+        if let Some(namespace) = &self.namespace {
+            for segment in namespace.split('/') {
+                encode(segment, "", f)?;
+                f.write_char('/')?;
+            }
+        }
+        encode(&self.name, "", f)?;
+        if let Some(version) = &self.version {
+            f.write_char('@')?;
+            encode(version, ":+", f)?;
+        }
+        for (n, (k, v)) in self.qualifiers.iter().enumerate() {
+            f.write_char(if n == 0 { '?' } else { '&' })?;
+            write!(f, "{k}=")?;
+            encode(v, ":/", f)?;
+        }
+        if let Some(subpath) = &self.subpath {
+            f.write_char('#')?;
+            encode(subpath, "/", f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let purl =
+            Purl::parse("pkg:rpm/redhat/openssl@1.1.1k-7.el8_6?arch=x86_64&repository_id=&epoch=1")
+                .unwrap();
+
+        assert_eq!(purl.ty, "rpm");
+        assert_eq!(purl.namespace.as_deref(), Some("redhat"));
+        assert_eq!(purl.name, "openssl");
+        assert_eq!(purl.version.as_deref(), Some("1.1.1k-7.el8_6"));
+        assert_eq!(
+            purl.to_string(),
+            "pkg:rpm/redhat/openssl@1.1.1k-7.el8_6?arch=x86_64&epoch=1"
+        );
+    }
+
+    #[test]
+    fn normalize() {
+        let a = Purl::parse("pkg:PyPI/Django_Rest@3.0").unwrap();
+        let b = Purl::parse("pkg:pypi/django-rest@3.0").unwrap();
+        assert_eq!(a, b);
+
+        let a = Purl::parse("pkg:npm/%40Angular/Core@1.0.0").unwrap();
+        assert_eq!(a.namespace.as_deref(), Some("@angular"));
+        assert_eq!(a.to_string(), "pkg:npm/%40angular/core@1.0.0");
+    }
+
+    #[test]
+    fn npm_scope() {
+        let purl = Purl::parse("pkg:npm/@angular/core").unwrap();
+        assert_eq!(purl.namespace.as_deref(), Some("@angular"));
+        assert_eq!(purl.name, "core");
+        assert_eq!(purl.version, None);
+
+        let purl = Purl::parse("pkg:npm/@angular/core@16.0.0").unwrap();
+        assert_eq!(purl.namespace.as_deref(), Some("@angular"));
+        assert_eq!(purl.name, "core");
+        assert_eq!(purl.version.as_deref(), Some("16.0.0"));
+    }
+
+    #[test]
+    fn matching() {
+        let vex = Purl::parse("pkg:rpm/redhat/openssl@1.1.1k-7.el8_6?arch=x86_64").unwrap();
+        let sbom =
+            Purl::parse("pkg:rpm/redhat/openssl@1.1.1k-7.el8_6?arch=x86_64&epoch=1").unwrap();
+        assert!(vex.matches(&sbom));
+        assert!(!sbom.matches(&vex));
+
+        let any = Purl::parse("pkg:rpm/redhat/openssl").unwrap();
+        assert!(any.matches(&sbom));
+
+        let range = VersionRange::from_nvd(None, None, None, Some("1:1.1.1k-8.el8_6"));
+        assert!(any.matches_range(&range, &sbom));
+    }
+
+    #[test]
+    fn epoch() {
+        let any = Purl::parse("pkg:rpm/redhat/openssl").unwrap();
+        let epoch0 = Purl::parse("pkg:rpm/redhat/openssl@1.1.1k-7.el8_6?epoch=0").unwrap();
+        let epoch1 = Purl::parse("pkg:rpm/redhat/openssl@1.1.1k-7.el8_6?epoch=1").unwrap();
+
+        assert_eq!(
+            epoch1.comparable_version().as_deref(),
+            Some("1:1.1.1k-7.el8_6")
+        );
+
+        // a later version, but a lower epoch
+        let range = VersionRange::from_nvd(None, None, None, Some("1.1.1k-8.el8_6"));
+        assert!(any.matches_range(&range, &epoch0));
+        assert!(!any.matches_range(&range, &epoch1));
+
+        let range = VersionRange::from_nvd(Some("1:1.1.1k-7.el8_6"), None, None, None);
+        assert!(!any.matches_range(&range, &epoch0));
+        assert!(any.matches_range(&range, &epoch1));
+
+        // other types don't have an epoch
+        let npm = Purl::parse("pkg:npm/foo@1.0.0?epoch=1").unwrap();
+        assert_eq!(npm.comparable_version().as_deref(), Some("1.0.0"));
+    }
+}
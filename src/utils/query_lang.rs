@@ -0,0 +1,521 @@
+//! A small query language, evaluated against the JSON form of SBOM and CSAF documents.
+//!
+//! ```text
+//! [from <path>] [where <condition> [and|or <condition>]...] [group by <path>] [count]
+//! ```
+//!
+//! A path is a sequence of fields, separated by dots, where `[*]` selects all elements of an
+//! array and `[n]` a single one, like `packages[*].externalRefs[*].referenceLocator`. The path of
+//! `from` selects the items from the document, all other paths are relative to an item. Without a
+//! `from`, the item is the document itself.
+//!
+//! Conditions compare a path to a literal (a string in double quotes, a number, `true`, `false`
+//! or `null`) with `=`, `!=`, `like` (a pattern supporting `*` and `?`) or `contains` (a
+//! substring, or an element of an array), or check it with `exists` or `missing`. A condition is
+//! met if any of the values selected by the path meets it, `and` binds stronger than `or`.
+//!
+//! For example, counting packages by supplier where the license is `NOASSERTION`:
+//!
+//! ```text
+//! from packages[*] where licenseConcluded = "NOASSERTION" group by supplier
+//! ```
+
+use crate::utils::glob::glob_match;
+use anyhow::{anyhow, bail};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// A keyword or a path
+    Word(String),
+    Literal(Value),
+    Eq,
+    Ne,
+}
+
+fn tokenize(input: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '=' => tokens.push(Token::Eq),
+            '!' => match chars.next() {
+                Some((_, '=')) => tokens.push(Token::Ne),
+                _ => bail!("Expected '=' after '!' at {start}"),
+            },
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => value.push(c),
+                            None => bail!("Unterminated string at {start}"),
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => bail!("Unterminated string at {start}"),
+                    }
+                }
+                tokens.push(Token::Literal(Value::String(value)));
+            }
+            _ => {
+                let mut end = input.len();
+                while let Some((i, c)) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '=' | '!' | '"') {
+                        end = *i;
+                        break;
+                    }
+                    chars.next();
+                }
+                let word = &input[start..end];
+                tokens.push(match word {
+                    "true" | "false" | "null" => Token::Literal(serde_json::from_str(word)?),
+                    _ if is_number(word) => Token::Literal(serde_json::from_str(word)?),
+                    _ => Token::Word(word.to_string()),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Check for a JSON number literal, so words like `inf` or `nan` remain field names
+fn is_number(word: &str) -> bool {
+    /// skip the leading digits, `None` if there are none
+    fn digits(s: &str) -> Option<&str> {
+        let rest = s.trim_start_matches(|c: char| c.is_ascii_digit());
+        (rest.len() < s.len()).then_some(rest)
+    }
+
+    let rest = word.strip_prefix('-').unwrap_or(word);
+    // no leading zeros
+    let rest = match rest.strip_prefix('0') {
+        Some(rest) => rest,
+        None if rest.starts_with(|c: char| c.is_ascii_digit()) => digits(rest).unwrap_or_default(),
+        None => return false,
+    };
+    let rest = match rest.strip_prefix('.') {
+        Some(fraction) => match digits(fraction) {
+            Some(rest) => rest,
+            None => return false,
+        },
+        None => rest,
+    };
+    let rest = match rest.strip_prefix(['e', 'E']) {
+        Some(exponent) => match digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)) {
+            Some(rest) => rest,
+            None => return false,
+        },
+        None => rest,
+    };
+
+    rest.is_empty()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+    /// all elements of an array
+    Each,
+}
+
+/// A path selecting values from a JSON value
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Path(Vec<Segment>);
+
+impl Path {
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        let mut segments = vec![];
+
+        // a single dot is the value itself
+        for part in input.split('.').filter(|part| !part.is_empty()) {
+            let (field, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+            if !field.is_empty() {
+                segments.push(Segment::Field(field.to_string()));
+            }
+            while !rest.is_empty() {
+                let end = rest
+                    .find(']')
+                    .ok_or_else(|| anyhow!("Missing ']' in path: {input}"))?;
+                segments.push(match &rest[1..end] {
+                    "*" => Segment::Each,
+                    index => Segment::Index(
+                        index
+                            .parse()
+                            .map_err(|_| anyhow!("Invalid index '{index}' in path: {input}"))?,
+                    ),
+                });
+                rest = &rest[end + 1..];
+                if !rest.is_empty() && !rest.starts_with('[') {
+                    bail!("Unexpected '{rest}' in path: {input}");
+                }
+            }
+        }
+
+        Ok(Self(segments))
+    }
+
+    /// All values selected by the path, missing values are skipped
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut values = vec![value];
+
+        for segment in &self.0 {
+            values = values
+                .into_iter()
+                .flat_map(|value| match segment {
+                    Segment::Field(name) => value.get(name).into_iter().collect(),
+                    Segment::Index(index) => value.get(index).into_iter().collect(),
+                    Segment::Each => match value {
+                        Value::Array(values) => values.iter().collect(),
+                        _ => vec![],
+                    },
+                })
+                .collect();
+        }
+
+        values
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Test {
+    Eq(Value),
+    Ne(Value),
+    Like(String),
+    Contains(Value),
+    Exists,
+    Missing,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Condition {
+    path: Path,
+    test: Test,
+}
+
+impl Condition {
+    fn matches(&self, item: &Value) -> bool {
+        let values = self.path.select(item);
+        let mut values = values.into_iter().filter(|value| !value.is_null());
+
+        match &self.test {
+            Test::Eq(expected) => values.any(|value| value == expected),
+            Test::Ne(expected) => !values.any(|value| value == expected),
+            Test::Like(pattern) => {
+                values.any(|value| value.as_str().is_some_and(|v| glob_match(pattern, v)))
+            }
+            Test::Contains(expected) => values.any(|value| match (value, expected) {
+                (Value::String(value), Value::String(expected)) => value.contains(expected),
+                (Value::Array(values), expected) => values.contains(expected),
+                _ => false,
+            }),
+            Test::Exists => values.next().is_some(),
+            Test::Missing => values.next().is_none(),
+        }
+    }
+}
+
+/// A parsed query
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Query {
+    from: Path,
+    /// alternatives, of conditions which all need to be met
+    filter: Vec<Vec<Condition>>,
+    group_by: Option<Path>,
+    count: bool,
+}
+
+struct Parser {
+    tokens: std::vec::IntoIter<Token>,
+    next: Option<Token>,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.next, Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        std::mem::replace(&mut self.next, self.tokens.next())
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn path(&mut self) -> anyhow::Result<Path> {
+        match self.advance() {
+            Some(Token::Word(word)) => Path::parse(&word),
+            other => bail!("Expected a path, found: {other:?}"),
+        }
+    }
+
+    fn literal(&mut self) -> anyhow::Result<Value> {
+        match self.advance() {
+            Some(Token::Literal(value)) => Ok(value),
+            other => bail!("Expected a literal, found: {other:?}"),
+        }
+    }
+
+    fn condition(&mut self) -> anyhow::Result<Condition> {
+        let path = self.path()?;
+        let test = match self.advance() {
+            Some(Token::Eq) => Test::Eq(self.literal()?),
+            Some(Token::Ne) => Test::Ne(self.literal()?),
+            Some(Token::Word(word)) => match word.to_ascii_lowercase().as_str() {
+                "like" => match self.literal()? {
+                    Value::String(pattern) => Test::Like(pattern),
+                    other => bail!("Expected a pattern, found: {other}"),
+                },
+                "contains" => Test::Contains(self.literal()?),
+                "exists" => Test::Exists,
+                "missing" => Test::Missing,
+                _ => bail!("Unknown operator: {word}"),
+            },
+            other => bail!("Expected an operator, found: {other:?}"),
+        };
+        Ok(Condition { path, test })
+    }
+}
+
+impl Query {
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        let mut tokens = tokenize(input)?.into_iter();
+        let next = tokens.next();
+        let mut parser = Parser { tokens, next };
+        let mut query = Self::default();
+
+        if parser.keyword("from") {
+            query.from = parser.path()?;
+        }
+
+        if parser.keyword("where") {
+            let mut all = vec![parser.condition()?];
+            loop {
+                if parser.keyword("and") {
+                    all.push(parser.condition()?);
+                } else if parser.keyword("or") {
+                    query.filter.push(std::mem::take(&mut all));
+                    all.push(parser.condition()?);
+                } else {
+                    break;
+                }
+            }
+            query.filter.push(all);
+        }
+
+        if parser.keyword("group") {
+            if !parser.keyword("by") {
+                bail!("Expected 'by' after 'group'");
+            }
+            query.group_by = Some(parser.path()?);
+        }
+
+        query.count = parser.keyword("count");
+
+        if let Some(token) = parser.next {
+            bail!("Unexpected {token:?}");
+        }
+
+        Ok(query)
+    }
+
+    /// Check if an item meets the conditions of the query
+    fn matches(&self, item: &Value) -> bool {
+        self.filter.is_empty()
+            || self
+                .filter
+                .iter()
+                .any(|all| all.iter().all(|condition| condition.matches(item)))
+    }
+
+    /// Evaluate the query against a document, adding the matching items to the result
+    pub fn evaluate(&self, document: &Value, result: &mut QueryResult) {
+        for item in self.from.select(document) {
+            if !self.matches(item) {
+                continue;
+            }
+
+            result.count += 1;
+
+            if let Some(group_by) = &self.group_by {
+                let keys = group_by.select(item);
+                if keys.is_empty() {
+                    *result.groups.entry("<none>".to_string()).or_default() += 1;
+                }
+                for key in keys {
+                    let key = match key {
+                        Value::String(key) => key.clone(),
+                        other => other.to_string(),
+                    };
+                    *result.groups.entry(key).or_default() += 1;
+                }
+            } else if !self.count {
+                result.values.push(item.clone());
+            }
+        }
+    }
+}
+
+/// The items matching a query, over all documents
+#[derive(Clone, Debug, Default)]
+pub struct QueryResult {
+    pub count: usize,
+    /// number of items, per group
+    pub groups: BTreeMap<String, usize>,
+    /// the items, unless grouped or counted
+    pub values: Vec<Value>,
+}
+
+impl QueryResult {
+    /// Add the items of another result, e.g. of another document
+    pub fn extend(&mut self, other: QueryResult) {
+        self.count += other.count;
+        for (group, count) in other.groups {
+            *self.groups.entry(group).or_default() += count;
+        }
+        self.values.extend(other.values);
+    }
+}
+
+impl Display for QueryResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for value in &self.values {
+            writeln!(f, "{value}")?;
+        }
+
+        let mut groups = self.groups.iter().collect::<Vec<_>>();
+        groups.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (group, count) in groups {
+            writeln!(f, "{count:>8} {group}")?;
+        }
+
+        write!(f, "{} matches", self.count)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn sbom() -> Value {
+        json!({
+            "name": "example",
+            "packages": [
+                {"name": "openssl", "supplier": "Organization: Red Hat", "licenseConcluded": "NOASSERTION"},
+                {"name": "zlib", "supplier": "Organization: Red Hat", "licenseConcluded": "Zlib"},
+                {"name": "libfoo", "licenseConcluded": "NOASSERTION",
+                    "externalRefs": [{"referenceType": "purl", "referenceLocator": "pkg:rpm/redhat/libfoo@1.0"}]},
+            ]
+        })
+    }
+
+    fn run(query: &str) -> QueryResult {
+        let mut result = QueryResult::default();
+        Query::parse(query).unwrap().evaluate(&sbom(), &mut result);
+        result
+    }
+
+    #[test]
+    fn test_path() {
+        let path = Path::parse("packages[*].externalRefs[0].referenceType").unwrap();
+        assert_eq!(path.select(&sbom()), vec![&json!("purl")]);
+        assert_eq!(Path::parse(".").unwrap().select(&sbom()), vec![&sbom()]);
+        assert!(Path::parse("packages[x]").is_err());
+        assert!(Path::parse("packages[*").is_err());
+    }
+
+    #[test]
+    fn test_group() {
+        let result =
+            run(r#"from packages[*] where licenseConcluded = "NOASSERTION" group by supplier"#);
+        assert_eq!(result.count, 2);
+        assert_eq!(
+            result.groups,
+            BTreeMap::from([
+                ("<none>".to_string(), 1),
+                ("Organization: Red Hat".to_string(), 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_conditions() {
+        let names = |query| {
+            run(query)
+                .values
+                .iter()
+                .map(|v| v["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(r#"from packages[*] where name like "lib*""#),
+            ["libfoo"]
+        );
+        assert_eq!(
+            names(r#"from packages[*] where supplier missing"#),
+            ["libfoo"]
+        );
+        assert_eq!(
+            names(r#"from packages[*] where name contains "l" and supplier exists"#),
+            ["openssl", "zlib"]
+        );
+        assert_eq!(
+            names(
+                r#"from packages[*] where name = "zlib" or externalRefs[*].referenceLocator like "pkg:rpm/*""#
+            ),
+            ["zlib", "libfoo"]
+        );
+        assert_eq!(
+            names(r#"from packages[*] where licenseConcluded != "NOASSERTION""#),
+            ["zlib"]
+        );
+    }
+
+    #[test]
+    fn test_count() {
+        let result = run("where name exists count");
+        assert_eq!(result.count, 1);
+        assert!(result.values.is_empty());
+    }
+
+    #[test]
+    fn test_number() {
+        for number in ["0", "-1", "42", "1.5", "-0.25", "1e3", "2.5E-2"] {
+            assert!(is_number(number), "{number}");
+        }
+        for word in [
+            "inf", "-inf", "nan", "infinity", "01", "1.", ".5", "+1", "1e", "-", "x1",
+        ] {
+            assert!(!is_number(word), "{word}");
+        }
+
+        let value = json!({"nan": 1, "inf": "x"});
+        let mut result = QueryResult::default();
+        Query::parse("where nan = 1 and inf exists count")
+            .unwrap()
+            .evaluate(&value, &mut result);
+        assert_eq!(result.count, 1);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Query::parse("from").is_err());
+        assert!(Query::parse(r#"where name = "open"#).is_err());
+        assert!(Query::parse("where name is 1").is_err());
+        assert!(Query::parse("group supplier").is_err());
+        assert!(Query::parse("from packages count extra").is_err());
+    }
+}
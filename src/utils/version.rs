@@ -0,0 +1,336 @@
+//! Version comparison and ranges, for matching CPE versions.
+
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Scheme {
+    /// `[epoch:]version[-release]`, compared like `rpmvercmp` does
+    Rpm,
+    /// `major.minor.patch[-pre][+build]`, missing numbers count as zero
+    Semver,
+}
+
+impl Scheme {
+    /// Guess the scheme from a version: anything which doesn't look like semver is treated as RPM
+    pub fn detect(version: &str) -> Self {
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        let numbers = core.split('.').collect::<Vec<_>>();
+
+        if numbers.len() == 3
+            && numbers
+                .iter()
+                .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        {
+            Self::Semver
+        } else {
+            Self::Rpm
+        }
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Self::Rpm => compare_evr(a, b),
+            Self::Semver => compare_semver(a, b),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bound {
+    pub version: String,
+    pub inclusive: bool,
+}
+
+/// A range of versions, like the `versionStart…`/`versionEnd…` fields of NVD configurations
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VersionRange {
+    pub start: Option<Bound>,
+    pub end: Option<Bound>,
+}
+
+impl VersionRange {
+    /// Create a range from the NVD fields, preferring the including variant when both are set.
+    pub fn from_nvd(
+        start_including: Option<&str>,
+        start_excluding: Option<&str>,
+        end_including: Option<&str>,
+        end_excluding: Option<&str>,
+    ) -> Self {
+        let bound = |including: Option<&str>, excluding: Option<&str>| {
+            including
+                .map(|v| (v, true))
+                .or(excluding.map(|v| (v, false)))
+                .map(|(version, inclusive)| Bound {
+                    version: version.to_string(),
+                    inclusive,
+                })
+        };
+
+        Self {
+            start: bound(start_including, start_excluding),
+            end: bound(end_including, end_excluding),
+        }
+    }
+
+    pub fn contains(&self, scheme: Scheme, version: &str) -> bool {
+        let start =
+            self.start
+                .as_ref()
+                .is_none_or(|start| match scheme.compare(version, &start.version) {
+                    Ordering::Greater => true,
+                    Ordering::Equal => start.inclusive,
+                    Ordering::Less => false,
+                });
+        let end = self
+            .end
+            .as_ref()
+            .is_none_or(|end| match scheme.compare(version, &end.version) {
+                Ordering::Less => true,
+                Ordering::Equal => end.inclusive,
+                Ordering::Greater => false,
+            });
+
+        start && end
+    }
+}
+
+/// Check if `prefix` is a prefix of `version`, ending at a component boundary.
+///
+/// So `8` is a prefix of `8`, `8.6` and `8-2`, but not of `80`.
+pub fn is_prefix(prefix: &str, version: &str) -> bool {
+    match version.strip_prefix(prefix) {
+        Some("") => true,
+        Some(rest) => rest.starts_with(|c: char| !c.is_ascii_alphanumeric()),
+        None => false,
+    }
+}
+
+/// Compare `[epoch:]version[-release]`
+pub fn compare_evr(a: &str, b: &str) -> Ordering {
+    fn split(evr: &str) -> (u64, &str, Option<&str>) {
+        let (epoch, vr) = match evr.split_once(':') {
+            Some((epoch, vr)) if epoch.chars().all(|c| c.is_ascii_digit()) => {
+                (epoch.parse().unwrap_or_default(), vr)
+            }
+            _ => (0, evr),
+        };
+        match vr.rsplit_once('-') {
+            Some((v, r)) => (epoch, v, Some(r)),
+            None => (epoch, vr, None),
+        }
+    }
+
+    let (ea, va, ra) = split(a);
+    let (eb, vb, rb) = split(b);
+
+    ea.cmp(&eb)
+        .then_with(|| rpmvercmp(va, vb))
+        .then_with(|| match (ra, rb) {
+            (Some(ra), Some(rb)) => rpmvercmp(ra, rb),
+            _ => Ordering::Equal,
+        })
+}
+
+/// The segment comparison of RPM
+pub fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let separator = |c: char| !c.is_ascii_alphanumeric() && c != '~' && c != '^';
+
+    let mut a = a;
+    let mut b = b;
+
+    while !a.is_empty() || !b.is_empty() {
+        a = a.trim_start_matches(separator);
+        b = b.trim_start_matches(separator);
+
+        // a tilde sorts before everything, even the end of the version
+        if a.starts_with('~') || b.starts_with('~') {
+            if !a.starts_with('~') {
+                return Ordering::Greater;
+            }
+            if !b.starts_with('~') {
+                return Ordering::Less;
+            }
+            a = &a[1..];
+            b = &b[1..];
+            continue;
+        }
+
+        // a caret sorts after the end of the version, but before everything else
+        if a.starts_with('^') || b.starts_with('^') {
+            if a.is_empty() {
+                return Ordering::Less;
+            }
+            if b.is_empty() {
+                return Ordering::Greater;
+            }
+            if !a.starts_with('^') {
+                return Ordering::Greater;
+            }
+            if !b.starts_with('^') {
+                return Ordering::Less;
+            }
+            a = &a[1..];
+            b = &b[1..];
+            continue;
+        }
+
+        if a.is_empty() || b.is_empty() {
+            break;
+        }
+
+        let numeric = a.starts_with(|c: char| c.is_ascii_digit());
+        let segment = |s: &str| -> usize {
+            s.find(|c: char| {
+                if numeric {
+                    !c.is_ascii_digit()
+                } else {
+                    !c.is_ascii_alphabetic()
+                }
+            })
+            .unwrap_or(s.len())
+        };
+
+        let (sa, ra) = a.split_at(segment(a));
+        let (sb, rb) = b.split_at(segment(b));
+
+        // numeric segments are newer than alphabetic ones
+        if sb.is_empty() {
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        let result = if numeric {
+            let sa = sa.trim_start_matches('0');
+            let sb = sb.trim_start_matches('0');
+            sa.len().cmp(&sb.len()).then_with(|| sa.cmp(sb))
+        } else {
+            sa.cmp(sb)
+        };
+
+        if result != Ordering::Equal {
+            return result;
+        }
+
+        a = ra;
+        b = rb;
+    }
+
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, _) => Ordering::Greater,
+    }
+}
+
+/// Compare semver-ish versions, ignoring build metadata
+pub fn compare_semver(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (Vec<&str>, Option<&str>) {
+        let version = version.split('+').next().unwrap_or_default();
+        match version.split_once('-') {
+            Some((core, pre)) => (core.split('.').collect(), Some(pre)),
+            None => (version.split('.').collect(), None),
+        }
+    }
+
+    fn identifier(a: &str, b: &str) -> Ordering {
+        match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        }
+    }
+
+    let (ca, pa) = split(a);
+    let (cb, pb) = split(b);
+
+    for i in 0..ca.len().max(cb.len()) {
+        let na = ca.get(i).copied().unwrap_or("0");
+        let nb = cb.get(i).copied().unwrap_or("0");
+        let result = match (na.parse::<u64>(), nb.parse::<u64>()) {
+            (Ok(na), Ok(nb)) => na.cmp(&nb),
+            _ => rpmvercmp(na, nb),
+        };
+        if result != Ordering::Equal {
+            return result;
+        }
+    }
+
+    match (pa, pb) {
+        (None, None) => Ordering::Equal,
+        // a pre-release is lower than the release
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(pa), Some(pb)) => {
+            let mut ia = pa.split('.');
+            let mut ib = pb.split('.');
+            loop {
+                match (ia.next(), ib.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(a), Some(b)) => match identifier(a, b) {
+                        Ordering::Equal => continue,
+                        result => return result,
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rpm() {
+        for (a, b, expected) in [
+            ("1.0", "1.0", Ordering::Equal),
+            ("1.0", "1.1", Ordering::Less),
+            ("1.10", "1.9", Ordering::Greater),
+            ("1.0a", "1.0", Ordering::Greater),
+            ("1.0~rc1", "1.0", Ordering::Less),
+            ("1.0^git1", "1.0", Ordering::Greater),
+            ("1.0^git1", "1.0.1", Ordering::Less),
+            ("1.1.1k-7.el8_6", "1.1.1k-5.el8_5", Ordering::Greater),
+            ("1:1.0", "2.0", Ordering::Greater),
+        ] {
+            assert_eq!(compare_evr(a, b), expected, "{a} / {b}");
+        }
+    }
+
+    #[test]
+    fn semver() {
+        for (a, b, expected) in [
+            ("1.2.3", "1.2.3+build", Ordering::Equal),
+            ("1.2.3-alpha", "1.2.3", Ordering::Less),
+            ("1.2.3-alpha.1", "1.2.3-alpha.beta", Ordering::Less),
+            ("1.10.0", "1.9.0", Ordering::Greater),
+            ("1.2", "1.2.0", Ordering::Equal),
+        ] {
+            assert_eq!(compare_semver(a, b), expected, "{a} / {b}");
+        }
+    }
+
+    #[test]
+    fn range() {
+        let range = VersionRange::from_nvd(Some("8.0"), None, None, Some("8.6"));
+
+        assert!(range.contains(Scheme::Rpm, "8.0"));
+        assert!(range.contains(Scheme::Rpm, "8.4"));
+        assert!(!range.contains(Scheme::Rpm, "8.6"));
+        assert!(!range.contains(Scheme::Rpm, "7.9"));
+
+        assert!(is_prefix("8", "8.6"));
+        assert!(is_prefix("8", "8"));
+        assert!(!is_prefix("8", "80"));
+    }
+}
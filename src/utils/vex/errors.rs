@@ -0,0 +1,173 @@
+//! Collect the documents which failed to be processed.
+
+use csaf::Csaf;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FailureKind {
+    Retrieval,
+    /// Failed digest or signature verification, with the kind of verification error
+    Verification(&'static str),
+    Parse,
+}
+
+impl Display for FailureKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Retrieval => write!(f, "retrieval"),
+            Self::Verification(kind) => write!(f, "verification ({kind})"),
+            Self::Parse => write!(f, "parse"),
+        }
+    }
+}
+
+/// A document which failed to be processed
+#[derive(Clone, Debug)]
+pub struct Failure {
+    /// The URL, or path, of the document
+    pub url: String,
+    pub kind: FailureKind,
+    /// The JSON path of a parse failure, if known
+    pub path: Option<String>,
+    pub message: String,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]", self.url, self.kind)?;
+        if let Some(path) = &self.path {
+            write!(f, " at {path}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// All failures of processing VEX documents
+#[derive(Clone, Debug, Default)]
+pub struct VexReport {
+    pub failures: Vec<Failure>,
+}
+
+impl VexReport {
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn add(
+        &mut self,
+        url: impl Display,
+        kind: FailureKind,
+        path: Option<String>,
+        message: impl Display,
+    ) {
+        let failure = Failure {
+            url: url.to_string(),
+            kind,
+            path,
+            message: message.to_string(),
+        };
+        log::warn!("Failed to process document {failure}");
+        self.failures.push(failure);
+    }
+
+    /// Log a summary of the failures, or fail in strict mode
+    pub fn check(&self, strict: bool) -> anyhow::Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        if strict {
+            anyhow::bail!("Failed to process VEX documents: {self}");
+        }
+        log::warn!("{self}");
+        Ok(())
+    }
+
+    /// Number of failures, per kind
+    pub fn kinds(&self) -> BTreeMap<FailureKind, usize> {
+        let mut result = BTreeMap::new();
+        for failure in &self.failures {
+            *result.entry(failure.kind).or_default() += 1;
+        }
+        result
+    }
+}
+
+impl Display for VexReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} documents failed", self.failures.len())?;
+        for (kind, count) in self.kinds() {
+            write!(f, ", {kind}: {count}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A failure parsing a document, with the JSON path it occurred at
+#[derive(Debug)]
+pub struct ParseError {
+    pub path: String,
+    pub error: serde_json::Error,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.error)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse a CSAF document, keeping track of the JSON path of a failure
+pub fn parse_csaf(data: &[u8]) -> Result<Csaf, ParseError> {
+    serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(data)).map_err(
+        |err| ParseError {
+            path: err.path().to_string(),
+            error: err.into_inner(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::Value;
+
+    const DOCUMENT: &str = include_str!("../../../tests/data/csaf/product-coverage.json");
+
+    fn report() -> VexReport {
+        let mut report = VexReport::default();
+        report.add("a.json", FailureKind::Parse, None, "invalid");
+        report.add(
+            "b.json",
+            FailureKind::Verification("missing-digest"),
+            None,
+            "no digest",
+        );
+        report.add("c.json", FailureKind::Parse, None, "invalid");
+        report
+    }
+
+    #[test]
+    fn test_parse_csaf() {
+        assert!(parse_csaf(DOCUMENT.as_bytes()).is_ok());
+
+        let mut csaf: Value = serde_json::from_str(DOCUMENT).unwrap();
+        csaf["document"]["tracking"]["revision_history"][0]["date"] = "yesterday".into();
+        let err = parse_csaf(&serde_json::to_vec(&csaf).unwrap()).unwrap_err();
+        assert_eq!(err.path, "document.tracking.revision_history[0].date");
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(VexReport::default().check(true).is_ok());
+        assert!(VexReport::default().check(false).is_ok());
+
+        let report = report();
+        assert!(report.check(false).is_ok());
+        assert_eq!(
+            report.check(true).unwrap_err().to_string(),
+            "Failed to process VEX documents: 3 documents failed, verification (missing-digest): 1, parse: 2"
+        );
+    }
+}
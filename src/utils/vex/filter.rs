@@ -0,0 +1,286 @@
+//! Select the advisories to process.
+
+use crate::utils::glob::glob_match;
+use crate::utils::vex::schema_name;
+use anyhow::anyhow;
+use csaf::Csaf;
+use csaf_walker::retrieve::RetrievedAdvisory;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// Filters of advisories, an empty filter matches all advisories
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    /// Only advisories modified at or after this time
+    pub modified_since: Option<OffsetDateTime>,
+    /// Only advisories with a publisher namespace starting with one of these
+    pub publishers: Vec<String>,
+    /// Only advisories with one of these TLP labels
+    pub tlp: Vec<String>,
+    /// Only advisories with a CVE matching one of these patterns, supporting `*` and `?`
+    pub cves: Vec<String>,
+}
+
+/// a comma separated list from the environment
+fn env_list(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string())
+        .collect()
+}
+
+/// parse an RFC 3339 timestamp, or a plain date as its start of the day in UTC
+fn parse_timestamp(value: &str) -> anyhow::Result<OffsetDateTime> {
+    let value = if value.len() == 10 {
+        format!("{value}T00:00:00Z")
+    } else {
+        value.to_string()
+    };
+    OffsetDateTime::parse(&value, &Rfc3339)
+        .map_err(|err| anyhow!("Invalid timestamp '{value}': {err}"))
+}
+
+impl Filter {
+    /// Read the filters from `CSAF_MODIFIED_SINCE`, `CSAF_PUBLISHER`, `CSAF_TLP` and `CSAF_CVE`.
+    ///
+    /// All but the timestamp are comma separated lists.
+    pub fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            modified_since: std::env::var("CSAF_MODIFIED_SINCE")
+                .ok()
+                .map(|value| parse_timestamp(&value))
+                .transpose()?,
+            publishers: env_list("CSAF_PUBLISHER"),
+            tlp: env_list("CSAF_TLP"),
+            cves: env_list("CSAF_CVE"),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modified_since.is_none()
+            && self.publishers.is_empty()
+            && self.tlp.is_empty()
+            && self.cves.is_empty()
+    }
+
+    /// The time an advisory was last modified, from the retrieval, or its current release
+    fn modified(retrieved: &RetrievedAdvisory, csaf: &Csaf) -> i64 {
+        retrieved
+            .metadata
+            .last_modification
+            .map(|modified| modified.unix_timestamp())
+            .unwrap_or_else(|| csaf.document.tracking.current_release_date.timestamp())
+    }
+
+    /// Check if an advisory was last modified before `modified_since`, by its retrieval alone.
+    ///
+    /// This allows skipping it before verifying and parsing it. Advisories without a modification
+    /// time are left to [`Self::matches`].
+    pub fn skips_retrieved(&self, retrieved: &RetrievedAdvisory) -> bool {
+        match (self.modified_since, retrieved.metadata.last_modification) {
+            (Some(since), Some(modified)) => modified < since,
+            _ => false,
+        }
+    }
+
+    /// Check if an advisory passes all filters
+    pub fn matches(&self, retrieved: &RetrievedAdvisory, csaf: &Csaf) -> bool {
+        if let Some(since) = self.modified_since {
+            if Self::modified(retrieved, csaf) < since.unix_timestamp() {
+                return false;
+            }
+        }
+
+        if !self.publishers.is_empty() {
+            let namespace = csaf.document.publisher.namespace.as_str();
+            if !self
+                .publishers
+                .iter()
+                .any(|publisher| namespace.starts_with(publisher.as_str()))
+            {
+                return false;
+            }
+        }
+
+        if !self.tlp.is_empty() {
+            // advisories without a label don't pass
+            let Some(label) = csaf
+                .document
+                .distribution
+                .as_ref()
+                .and_then(|distribution| distribution.tlp.as_ref())
+                .map(|tlp| schema_name(&tlp.label))
+            else {
+                return false;
+            };
+            if !self.tlp.iter().any(|tlp| tlp.eq_ignore_ascii_case(&label)) {
+                return false;
+            }
+        }
+
+        if !self.cves.is_empty() {
+            let mut cves = csaf
+                .vulnerabilities
+                .iter()
+                .flatten()
+                .filter_map(|vulnerability| vulnerability.cve.as_deref());
+            if !cves.any(|cve| self.cves.iter().any(|pattern| glob_match(pattern, cve))) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use csaf_walker::discover::DiscoveredAdvisory;
+    use csaf_walker::retrieve::RetrievalMetadata;
+    use serde_json::Value;
+
+    fn csaf(modify: impl FnOnce(&mut Value)) -> Csaf {
+        let mut csaf: Value = serde_json::from_str(include_str!(
+            "../../../tests/data/csaf/product-coverage.json"
+        ))
+        .unwrap();
+        modify(&mut csaf);
+        serde_json::from_value(csaf).unwrap()
+    }
+
+    fn retrieved(last_modification: Option<OffsetDateTime>) -> RetrievedAdvisory {
+        RetrievedAdvisory {
+            discovered: DiscoveredAdvisory {
+                url: "https://example.com/product-coverage.json".parse().unwrap(),
+            },
+            data: Default::default(),
+            signature: None,
+            sha256: None,
+            sha512: None,
+            metadata: RetrievalMetadata {
+                last_modification,
+                etag: None,
+            },
+        }
+    }
+
+    fn list(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_from_env() {
+        std::env::set_var("CSAF_MODIFIED_SINCE", "2023-06-01");
+        std::env::set_var("CSAF_PUBLISHER", "https://example.com, ,https://redhat.com");
+        std::env::set_var("CSAF_TLP", "white");
+        std::env::remove_var("CSAF_CVE");
+        let filter = Filter::from_env().unwrap();
+        assert_eq!(
+            filter.modified_since,
+            Some(parse_timestamp("2023-06-01T00:00:00Z").unwrap())
+        );
+        assert_eq!(
+            filter.publishers,
+            list(&["https://example.com", "https://redhat.com"])
+        );
+        assert_eq!(filter.tlp, list(&["white"]));
+        assert!(filter.cves.is_empty());
+        assert!(!filter.is_empty());
+
+        std::env::set_var("CSAF_MODIFIED_SINCE", "June 2023");
+        assert!(Filter::from_env().is_err());
+
+        for name in [
+            "CSAF_MODIFIED_SINCE",
+            "CSAF_PUBLISHER",
+            "CSAF_TLP",
+            "CSAF_CVE",
+        ] {
+            std::env::remove_var(name);
+        }
+        assert!(Filter::from_env().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_modified_since() {
+        let filter = Filter {
+            modified_since: Some(parse_timestamp("2023-06-01").unwrap()),
+            ..Default::default()
+        };
+        let csaf = csaf(|_| {});
+
+        // the current release date, without a retrieval time
+        assert!(!filter.skips_retrieved(&retrieved(None)));
+        assert!(!filter.matches(&retrieved(None), &csaf));
+
+        let before = parse_timestamp("2023-05-31").unwrap();
+        let after = parse_timestamp("2023-06-02").unwrap();
+        assert!(filter.skips_retrieved(&retrieved(Some(before))));
+        assert!(!filter.skips_retrieved(&retrieved(Some(after))));
+        assert!(filter.matches(&retrieved(Some(after)), &csaf));
+    }
+
+    #[test]
+    fn test_publishers() {
+        let csaf = csaf(|_| {});
+        let filter = |publishers| Filter {
+            publishers: list(publishers),
+            ..Default::default()
+        };
+
+        assert!(filter(&[]).matches(&retrieved(None), &csaf));
+        assert!(filter(&["https://example"]).matches(&retrieved(None), &csaf));
+        assert!(
+            filter(&["https://redhat.com", "https://example.com"]).matches(&retrieved(None), &csaf)
+        );
+        assert!(!filter(&["https://redhat.com"]).matches(&retrieved(None), &csaf));
+    }
+
+    #[test]
+    fn test_tlp() {
+        let filter = Filter {
+            tlp: list(&["white", "GREEN"]),
+            ..Default::default()
+        };
+
+        // without a label
+        assert!(!filter.matches(&retrieved(None), &csaf(|_| {})));
+
+        let labelled = |label: &str| {
+            csaf(|csaf| {
+                csaf["document"]["distribution"] = serde_json::json!({ "tlp": { "label": label } });
+            })
+        };
+        assert!(filter.matches(&retrieved(None), &labelled("WHITE")));
+        assert!(filter.matches(&retrieved(None), &labelled("GREEN")));
+        assert!(!filter.matches(&retrieved(None), &labelled("RED")));
+    }
+
+    #[test]
+    fn test_cves() {
+        let csaf = csaf(|_| {});
+        let filter = |cves| Filter {
+            cves: list(cves),
+            ..Default::default()
+        };
+
+        assert!(filter(&["CVE-2023-0286"]).matches(&retrieved(None), &csaf));
+        assert!(filter(&["CVE-2023-*"]).matches(&retrieved(None), &csaf));
+        assert!(filter(&["CVE-2022-*", "CVE-2023-028?"]).matches(&retrieved(None), &csaf));
+        assert!(!filter(&["CVE-2022-*"]).matches(&retrieved(None), &csaf));
+        assert!(!filter(&["CVE-2023-028"]).matches(&retrieved(None), &csaf));
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(
+            parse_timestamp("2023-06-01").unwrap(),
+            parse_timestamp("2023-06-01T00:00:00Z").unwrap()
+        );
+        assert!(parse_timestamp("June 2023").is_err());
+    }
+}
@@ -0,0 +1,451 @@
+//! Match VEX products to SBOM packages, using all product identification helpers.
+
+use crate::utils::cpe_match::{self, VersionMatch};
+use crate::utils::cpe_name::Wfn;
+use crate::utils::purl::Purl;
+use crate::utils::vex::product::ProductIndex;
+use crate::utils::vex::resolve::Product;
+use crate::utils::vex::schema_name;
+use csaf::definitions::FullProductName;
+use serde_json::Value;
+use spdx_rs::models::{PackageInformation, SPDX};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// The kind of evidence a match is based on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Evidence {
+    Hash,
+    Purl,
+    SbomUrl,
+    Cpe,
+    SerialNumber,
+    Sku,
+    ModelNumber,
+    GenericUri,
+}
+
+impl Evidence {
+    /// How much a match of this kind counts
+    pub fn score(&self) -> u32 {
+        match self {
+            Self::Hash => 100,
+            Self::Purl => 80,
+            Self::SbomUrl => 70,
+            Self::Cpe => 50,
+            Self::SerialNumber => 40,
+            Self::Sku => 30,
+            Self::ModelNumber => 20,
+            Self::GenericUri => 10,
+        }
+    }
+}
+
+/// The identification helpers of a CSAF product, parsed and normalized
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Helpers {
+    pub cpes: Vec<Wfn>,
+    pub purls: Vec<Purl>,
+    /// algorithm and value, both lowercase
+    pub hashes: BTreeSet<(String, String)>,
+    pub model_numbers: BTreeSet<String>,
+    pub sbom_urls: BTreeSet<String>,
+    pub serial_numbers: BTreeSet<String>,
+    pub skus: BTreeSet<String>,
+    pub x_generic_uris: BTreeSet<String>,
+}
+
+/// normalize a hash algorithm name, so that `SHA-256`, `sha256` and `SHA256` are the same
+fn algorithm(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// the values of an optional list of a helper
+fn strings<T: ToString>(values: &Option<Vec<T>>) -> BTreeSet<String> {
+    values.iter().flatten().map(|v| v.to_string()).collect()
+}
+
+impl Helpers {
+    /// Collect the helpers of a product.
+    ///
+    /// Helpers which fail to parse are logged and skipped.
+    pub fn from_product(product: &FullProductName) -> Self {
+        let Some(helper) = &product.product_identification_helper else {
+            return Self::default();
+        };
+        let id = &product.product_id.0;
+
+        let cpes = helper
+            .cpe
+            .iter()
+            .map(|cpe| cpe.to_string())
+            .filter_map(|cpe| match Wfn::parse(&cpe) {
+                Ok(wfn) => Some(wfn),
+                Err(err) => {
+                    log::warn!("Failed to parse CPE of {id} ({cpe}): {err:?}");
+                    None
+                }
+            })
+            .collect();
+        let purls = helper
+            .purl
+            .iter()
+            .filter_map(|purl| match Purl::parse(purl.as_str()) {
+                Ok(purl) => Some(purl),
+                Err(err) => {
+                    log::warn!("Failed to parse purl of {id} ({purl}): {err}");
+                    None
+                }
+            })
+            .collect();
+
+        let hashes = helper
+            .hashes
+            .iter()
+            .flatten()
+            .flat_map(|h| &h.file_hashes)
+            .map(|h| (algorithm(&h.algorithm), h.value.to_ascii_lowercase()))
+            .collect();
+
+        // the model keeps generic URIs as plain JSON, their `uri` is the one to match
+        let x_generic_uris = helper
+            .x_generic_uris
+            .iter()
+            .flatten()
+            .filter_map(|u| u.get("uri").and_then(Value::as_str))
+            .map(|s| s.to_string())
+            .collect();
+
+        Self {
+            cpes,
+            purls,
+            hashes,
+            model_numbers: strings(&helper.model_numbers),
+            sbom_urls: strings(&helper.sbom_urls),
+            serial_numbers: strings(&helper.serial_numbers),
+            skus: strings(&helper.skus),
+            x_generic_uris,
+        }
+    }
+
+    /// Collect the helpers of a product ID, from the product tree
+    pub fn from_index(index: &ProductIndex, product_id: &str) -> Self {
+        index
+            .product(product_id)
+            .map(Self::from_product)
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cpes.is_empty()
+            && self.purls.is_empty()
+            && self.hashes.is_empty()
+            && self.model_numbers.is_empty()
+            && self.sbom_urls.is_empty()
+            && self.serial_numbers.is_empty()
+            && self.skus.is_empty()
+            && self.x_generic_uris.is_empty()
+    }
+
+    /// Evaluate the evidence for a package of an SBOM
+    pub fn evidence(&self, sbom: &SbomIndex, package: &PackageIdentifiers) -> BTreeSet<Evidence> {
+        let mut result = BTreeSet::new();
+
+        if package.main && self.sbom_urls.contains(sbom.namespace()) {
+            result.insert(Evidence::SbomUrl);
+        }
+        if !self.hashes.is_disjoint(&package.hashes) {
+            result.insert(Evidence::Hash);
+        }
+        if package.cpes.iter().any(|cpe| {
+            self.cpes
+                .iter()
+                .any(|c| cpe_match::is_match_with(c, cpe, &VersionMatch::Prefix))
+        }) {
+            result.insert(Evidence::Cpe);
+        }
+        if package
+            .purls
+            .iter()
+            .any(|purl| self.purls.iter().any(|p| p.matches(purl)))
+        {
+            result.insert(Evidence::Purl);
+        }
+
+        for (set, kind, evidence) in [
+            (
+                &self.serial_numbers,
+                Identifier::SerialNumber,
+                Evidence::SerialNumber,
+            ),
+            (&self.skus, Identifier::Sku, Evidence::Sku),
+            (
+                &self.model_numbers,
+                Identifier::ModelNumber,
+                Evidence::ModelNumber,
+            ),
+        ] {
+            if set
+                .iter()
+                .any(|s| package.identifiers.contains(&(kind, s.as_str())))
+            {
+                result.insert(evidence);
+            }
+        }
+        if self
+            .x_generic_uris
+            .iter()
+            .any(|uri| package.uris.contains(uri.as_str()))
+        {
+            result.insert(Evidence::GenericUri);
+        }
+
+        result
+    }
+}
+
+/// The kinds of identifiers an SBOM package can only carry as an external reference
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+    SerialNumber,
+    Sku,
+    ModelNumber,
+}
+
+impl Identifier {
+    /// detect the kind from the type of an external reference, e.g. `serialNumber` or `sku`
+    fn from_reference_type(ty: &str) -> Option<Self> {
+        match algorithm(ty).as_str() {
+            "serialnumber" => Some(Self::SerialNumber),
+            "sku" => Some(Self::Sku),
+            "modelnumber" => Some(Self::ModelNumber),
+            _ => None,
+        }
+    }
+}
+
+/// The identifiers of an SBOM package, parsed once for matching many products
+#[derive(Clone, Debug)]
+pub struct PackageIdentifiers<'a> {
+    pub package: &'a PackageInformation,
+    /// If the package is one of the main packages of the SBOM
+    pub main: bool,
+    cpes: Vec<Wfn>,
+    purls: Vec<Purl>,
+    hashes: BTreeSet<(String, String)>,
+    identifiers: BTreeSet<(Identifier, &'a str)>,
+    uris: BTreeSet<&'a str>,
+}
+
+impl<'a> PackageIdentifiers<'a> {
+    fn new(spdx: &SPDX, package: &'a PackageInformation) -> Self {
+        let mut result = Self {
+            package,
+            main: spdx
+                .document_creation_information
+                .document_describes
+                .contains(&package.package_spdx_identifier),
+            cpes: vec![],
+            purls: vec![],
+            hashes: package
+                .package_checksum
+                .iter()
+                .map(|c| {
+                    (
+                        algorithm(&schema_name(&c.algorithm)),
+                        c.value.to_ascii_lowercase(),
+                    )
+                })
+                .collect(),
+            identifiers: BTreeSet::new(),
+            uris: BTreeSet::new(),
+        };
+
+        result
+            .uris
+            .insert(package.package_download_location.as_str());
+        result.uris.extend(package.package_home_page.as_deref());
+
+        for ext in &package.external_reference {
+            let locator = ext.reference_locator.as_str();
+            match ext.reference_type.as_str() {
+                "cpe22Type" | "cpe23Type" => result.cpes.extend(Wfn::parse(locator).ok()),
+                "purl" => result.purls.extend(Purl::parse(locator).ok()),
+                ty => match Identifier::from_reference_type(ty) {
+                    Some(kind) => {
+                        result.identifiers.insert((kind, locator));
+                    }
+                    None => {
+                        result.uris.insert(locator);
+                    }
+                },
+            }
+        }
+
+        result
+    }
+}
+
+/// The packages of an SBOM, with their identifiers parsed once.
+///
+/// Matching many products against an SBOM should go through this, instead of parsing the
+/// identifiers of every package again for every product.
+#[derive(Clone, Debug)]
+pub struct SbomIndex<'a> {
+    pub spdx: &'a SPDX,
+    pub packages: Vec<PackageIdentifiers<'a>>,
+}
+
+impl<'a> SbomIndex<'a> {
+    pub fn new(spdx: &'a SPDX) -> Self {
+        Self {
+            spdx,
+            packages: spdx
+                .package_information
+                .iter()
+                .map(|package| PackageIdentifiers::new(spdx, package))
+                .collect(),
+        }
+    }
+
+    pub fn namespace(&self) -> &'a str {
+        &self
+            .spdx
+            .document_creation_information
+            .spdx_document_namespace
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PackageMatch<'a> {
+    pub package: &'a PackageInformation,
+    /// If the package is one of the main packages of the SBOM
+    pub main: bool,
+    pub evidence: BTreeSet<Evidence>,
+    pub score: u32,
+}
+
+/// Find the packages of an SBOM identified by the helpers, best match first
+pub fn match_packages<'a>(helpers: &Helpers, sbom: &SbomIndex<'a>) -> Vec<PackageMatch<'a>> {
+    if helpers.is_empty() {
+        return vec![];
+    }
+
+    let mut result = sbom
+        .packages
+        .iter()
+        .filter_map(|package| {
+            let evidence = helpers.evidence(sbom, package);
+            let score = evidence.iter().map(Evidence::score).sum();
+            (score > 0).then_some(PackageMatch {
+                package: package.package,
+                main: package.main,
+                evidence,
+                score,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    result.sort_by_key(|m| std::cmp::Reverse(m.score));
+
+    result
+}
+
+/// A platform a component is related to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Platform {
+    /// The category of the relationship, like `installed_on`
+    pub category: String,
+    pub product: Product,
+}
+
+/// A product, which may be a component related to one or more platforms
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProductRef {
+    pub product_id: String,
+    pub component: Product,
+    /// The products the component is related to, one for each relationship defining the product
+    pub platforms: Vec<Platform>,
+}
+
+impl ProductRef {
+    /// Resolve a product ID, following all relationships defining it.
+    ///
+    /// The helpers of the relationship's own product name are used when the referenced component
+    /// has none.
+    pub fn resolve(index: &ProductIndex, product_id: &str) -> Self {
+        let relations = index.relations(product_id);
+
+        let components = relations
+            .iter()
+            .map(|rel| Product::from_index(index, &rel.product_reference.0))
+            .collect::<Vec<_>>();
+        let mut component = components
+            .iter()
+            .find(|product| !product.helpers.is_empty())
+            .or(components.first())
+            .cloned()
+            .unwrap_or_else(|| Product::from_index(index, product_id));
+        if component.helpers.is_empty() {
+            component.helpers = Helpers::from_index(index, product_id);
+        }
+
+        Self {
+            product_id: product_id.to_string(),
+            component,
+            platforms: relations
+                .iter()
+                .map(|rel| Platform {
+                    category: schema_name(&rel.category),
+                    product: Product::from_index(index, &rel.relates_to_product_reference.0),
+                })
+                .collect(),
+        }
+    }
+
+    /// Find the matching packages of an SBOM.
+    ///
+    /// If the product has platforms, one of the main packages of the SBOM must match one of the
+    /// platforms. Without a component, the matching main packages are returned.
+    pub fn match_sbom<'a>(&self, sbom: &SbomIndex<'a>) -> Vec<PackageMatch<'a>> {
+        let platforms = self
+            .platforms
+            .iter()
+            .map(|p| &p.product.helpers)
+            .filter(|helpers| !helpers.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut platform = platforms
+            .iter()
+            .flat_map(|helpers| match_packages(helpers, sbom))
+            .filter(|m| m.main)
+            .collect::<Vec<_>>();
+        if !platforms.is_empty() && platform.is_empty() {
+            return vec![];
+        }
+        // a main package may match more than one platform, keep its best match
+        platform.sort_by_key(|m| std::cmp::Reverse(m.score));
+        let mut seen = BTreeSet::new();
+        platform.retain(|m| seen.insert(&m.package.package_spdx_identifier));
+
+        if self.component.helpers.is_empty() {
+            platform
+        } else {
+            match_packages(&self.component.helpers, sbom)
+        }
+    }
+}
+
+impl Display for ProductRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.component)?;
+        for (n, platform) in self.platforms.iter().enumerate() {
+            f.write_str(if n == 0 { " on " } else { ", " })?;
+            write!(f, "{}", platform.product)?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,16 @@
+pub mod errors;
+pub mod filter;
+pub mod identify;
 pub mod product;
+pub mod query;
+pub mod report;
+pub mod resolve;
+pub mod statement;
+pub mod status;
+pub mod validate;
+pub mod verify;
 
+use anyhow::anyhow;
 use async_trait::async_trait;
 use csaf::Csaf;
 use csaf_walker::{
@@ -8,11 +19,23 @@ use csaf_walker::{
     source::FileSource,
     walker::Walker,
 };
+use errors::{parse_csaf, FailureKind, VexReport};
+use filter::Filter;
 use indicatif::{MultiProgress, ProgressStyle};
+use serde::Serialize;
+use statement::VexDocument;
 use std::borrow::Cow;
 use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use tokio::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinSet;
+use verify::{VerificationError, Verifier};
+
+/// The number of retrieved advisories waiting to be processed
+const QUEUE_SIZE: usize = 64;
 
 pub struct MultiIndicatif(MultiProgress);
 
@@ -41,9 +64,35 @@ impl ProgressBar for IndicatifProgressBar {
     }
 }
 
-#[async_trait(?Send)]
-pub trait VexTask {
-    async fn process(&mut self, advisory: ParsedAdvisory) -> anyhow::Result<()>;
+/// the string form of a value, as used in the CSAF schema, like the name of an enum value
+pub fn schema_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => "unknown".to_string(),
+    }
+}
+
+#[async_trait]
+pub trait VexTask: Send + Sync {
+    /// If the task processes statements, see [`VexTask::process_statements`].
+    ///
+    /// Converting documents into statements resolves every product, so that it's only done for
+    /// tasks which opt in.
+    const WANTS_STATEMENTS: bool = false;
+
+    /// Process an advisory.
+    ///
+    /// Advisories are processed concurrently, so tasks need to synchronize access to their state.
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()>;
+
+    /// Process the statements of a document, of any format.
+    ///
+    /// CSAF documents are passed to both this and [`VexTask::process`], OpenVEX and CycloneDX
+    /// documents only to this. Only called if [`VexTask::WANTS_STATEMENTS`] is set.
+    async fn process_statements(&self, document: &VexDocument) -> anyhow::Result<()> {
+        let _ = document;
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -66,41 +115,435 @@ impl DerefMut for ParsedAdvisory {
     }
 }
 
-pub async fn run_vex<T>(multi: &MultiProgress, task: &mut T) -> anyhow::Result<()>
+/// Options for processing VEX documents, read from the environment
+#[derive(Clone, Debug)]
+pub struct VexOptions {
+    /// The CSAF documents, from `CSAF_DATA`
+    pub base: PathBuf,
+    /// The OpenVEX and CycloneDX documents, from `VEX_DATA`
+    pub other: PathBuf,
+    /// Verify the digests of CSAF documents, if `CSAF_VERIFY` is set
+    pub verify_digests: bool,
+    /// Verify the signatures of CSAF documents against the keyring from `CSAF_KEYRING`
+    pub keyring: Option<PathBuf>,
+    /// Skip documents failing verification, rather than only reporting them, if
+    /// `CSAF_SKIP_INVALID` is set
+    pub skip_invalid: bool,
+    /// Fail if any document failed to be processed, if `CSAF_STRICT` is set
+    pub strict: bool,
+    /// Only process the advisories passing this filter
+    pub filter: Filter,
+}
+
+/// check if a flag is set in the environment
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .is_ok_and(|value| matches!(value.to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+}
+
+impl VexOptions {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            base: std::env::var_os("CSAF_DATA")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| "data/vex".into()),
+            other: std::env::var_os("VEX_DATA")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| "data/vex-documents".into()),
+            verify_digests: env_flag("CSAF_VERIFY"),
+            keyring: std::env::var_os("CSAF_KEYRING").map(PathBuf::from),
+            skip_invalid: env_flag("CSAF_SKIP_INVALID"),
+            strict: env_flag("CSAF_STRICT"),
+            filter: Filter::from_env()?,
+        })
+    }
+}
+
+/// State shared by the concurrent processing of advisories
+struct Processing<T> {
+    task: T,
+    verifier: Verifier,
+    skip_invalid: bool,
+    /// documents failing to be retrieved, verified, or parsed
+    report: Mutex<VexReport>,
+    filter: Filter,
+    /// number of advisories not passing the filter
+    filtered: AtomicUsize,
+}
+
+/// Process all advisories, with the options from the environment.
+///
+/// Documents failing to be processed are logged, and fail the run in strict mode.
+pub async fn run_vex<T>(multi: &MultiProgress, task: T) -> anyhow::Result<T>
 where
-    T: VexTask,
+    T: VexTask + 'static,
+{
+    let options = VexOptions::from_env()?;
+    let (task, report) = run_vex_with(multi, &options, task).await?;
+    report.check(options.strict)?;
+    Ok(task)
+}
+
+/// Process all advisories of a directory, like [`run_vex`], instead of the one from `CSAF_DATA`.
+pub async fn run_vex_in<T>(multi: &MultiProgress, base: &Path, task: T) -> anyhow::Result<T>
+where
+    T: VexTask + 'static,
+{
+    let options = VexOptions {
+        base: base.to_path_buf(),
+        ..VexOptions::from_env()?
+    };
+    let (task, report) = run_vex_with(multi, &options, task).await?;
+    report.check(options.strict)?;
+    Ok(task)
+}
+
+/// Process all advisories, concurrently, and hand back the task and the documents which failed
+/// to be processed when done.
+pub async fn run_vex_with<T>(
+    multi: &MultiProgress,
+    options: &VexOptions,
+    task: T,
+) -> anyhow::Result<(T, VexReport)>
+where
+    T: VexTask + 'static,
 {
     let progress = Progress::new(MultiIndicatif(multi.clone()));
 
-    let base = std::env::var("CSAF_DATA").unwrap_or_else(|_| "data/vex".to_string());
-    log::info!("Loading VEX from: {base}");
-    let source = FileSource::new(base).unwrap();
+    log::info!("Loading VEX from: {}", options.base.display());
+    let source = FileSource::new(&options.base).map_err(|err| {
+        anyhow!(
+            "Failed to open CSAF documents {}: {err}",
+            options.base.display()
+        )
+    })?;
 
-    let task = Mutex::new(task);
+    let processing = Arc::new(Processing {
+        task,
+        verifier: Verifier::new(options.verify_digests, options.keyring.as_deref())?,
+        skip_invalid: options.skip_invalid,
+        report: Mutex::new(VexReport::default()),
+        filter: options.filter.clone(),
+        filtered: AtomicUsize::new(0),
+    });
+    let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+    let processed = tokio::spawn(process_advisories(processing.clone(), rx));
 
-    Walker::new(source.clone())
+    let walked = Walker::new(source.clone())
         .with_progress(progress.into())
         .walk(RetrievingVisitor::new(
             source.clone(),
             |retrieved: Result<RetrievedAdvisory, RetrievalError>| async {
                 match retrieved {
-                    Ok(retrieved) => match serde_json::from_slice(&retrieved.data) {
-                        Ok(csaf) => {
-                            task.lock()
-                                .await
-                                .process(ParsedAdvisory { retrieved, csaf })
-                                .await?
-                        }
-                        Err(err) => {
-                            log::warn!("Failed to parse document: {err}");
-                        }
-                    },
-                    Err(err) => log::warn!("Failed to retrieve document: {err}"),
+                    Ok(retrieved) => tx
+                        .send(retrieved)
+                        .await
+                        .map_err(|_| anyhow!("Processing of advisories stopped"))?,
+                    Err(err) => processing.report.lock().await.add(
+                        &err.discovered().url,
+                        FailureKind::Retrieval,
+                        None,
+                        &err,
+                    ),
                 }
                 Ok::<_, anyhow::Error>(())
             },
         ))
-        .await?;
+        .await;
+
+    // processing errors are the cause of the walker failing to send
+    drop(tx);
+    processed.await??;
+    walked?;
+
+    if !options.filter.is_empty() {
+        log::info!(
+            "Skipped {} advisories not matching the filter",
+            processing.filtered.load(Ordering::Relaxed)
+        );
+    }
+
+    run_other_vex(&options.other, &processing).await?;
+
+    Arc::into_inner(processing)
+        .map(|processing| (processing.task, processing.report.into_inner()))
+        .ok_or_else(|| anyhow!("VEX task is still in use"))
+}
+
+/// Process advisories as they are received, running up to one per core at a time
+async fn process_advisories<T>(
+    processing: Arc<Processing<T>>,
+    mut rx: mpsc::Receiver<RetrievedAdvisory>,
+) -> anyhow::Result<()>
+where
+    T: VexTask + 'static,
+{
+    let limit = std::thread::available_parallelism()
+        .map(usize::from)
+        .unwrap_or(1);
+    let mut running = JoinSet::new();
+
+    while let Some(retrieved) = rx.recv().await {
+        if running.len() >= limit {
+            if let Some(result) = running.join_next().await {
+                result??;
+            }
+        }
+        running.spawn(process_advisory(processing.clone(), retrieved));
+    }
+
+    while let Some(result) = running.join_next().await {
+        result??;
+    }
+
+    Ok(())
+}
+
+async fn process_advisory<T>(
+    processing: Arc<Processing<T>>,
+    retrieved: RetrievedAdvisory,
+) -> anyhow::Result<()>
+where
+    T: VexTask + 'static,
+{
+    if processing.filter.skips_retrieved(&retrieved) {
+        processing.filtered.fetch_add(1, Ordering::Relaxed);
+        return Ok(());
+    }
+
+    // verifying, parsing and converting is CPU bound, so keep it off the async workers
+    let shared = processing.clone();
+    let Some((retrieved, csaf, document)) = tokio::task::spawn_blocking(move || {
+        // only report verification failures of advisories passing the filter
+        let verified = shared.verifier.verify(&retrieved);
+        let report_verification = |err: &VerificationError| {
+            shared.report.blocking_lock().add(
+                &retrieved.url,
+                FailureKind::Verification(err.kind()),
+                None,
+                err,
+            );
+        };
+
+        match parse_csaf(&retrieved.data) {
+            Ok(csaf) if !shared.filter.matches(&retrieved, &csaf) => {
+                shared.filtered.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Ok(csaf) => {
+                if let Err(err) = &verified {
+                    report_verification(err);
+                    if shared.skip_invalid {
+                        return None;
+                    }
+                }
+                let document = T::WANTS_STATEMENTS.then(|| VexDocument::from_csaf(&csaf));
+                Some((retrieved, csaf, document))
+            }
+            Err(err) => {
+                if let Err(err) = &verified {
+                    report_verification(err);
+                }
+                shared.report.blocking_lock().add(
+                    &retrieved.url,
+                    FailureKind::Parse,
+                    Some(err.path),
+                    &err.error,
+                );
+                None
+            }
+        }
+    })
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let task = &processing.task;
+    if let Some(document) = document {
+        task.process_statements(&document).await?;
+    }
+    task.process(ParsedAdvisory { retrieved, csaf }).await
+}
+
+/// Process the OpenVEX and CycloneDX documents, which are not part of a CSAF provider
+async fn run_other_vex<T>(base: &Path, processing: &Processing<T>) -> anyhow::Result<()>
+where
+    T: VexTask,
+{
+    if !T::WANTS_STATEMENTS {
+        return Ok(());
+    }
+
+    let mut entries = match tokio::fs::read_dir(base).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    log::info!("Loading other VEX documents from: {}", base.display());
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+
+        let data = tokio::fs::read(&path).await?;
+        match tokio::task::spawn_blocking(move || VexDocument::parse(&data)).await? {
+            Ok(document) => processing.task.process_statements(&document).await?,
+            Err(err) => {
+                processing
+                    .report
+                    .lock()
+                    .await
+                    .add(path.display(), FailureKind::Parse, None, &err)
+            }
+        }
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tasks::vex::query::QueryCsaf;
+    use crate::utils::query_lang::Query;
+    use crate::utils::vex::report::CollectStatements;
+    use csaf_walker::discover::DiscoveredAdvisory;
+    use csaf_walker::retrieve::RetrievalMetadata;
+    use serde_json::Value;
+    use time::OffsetDateTime;
+
+    /// copies of the test document, with different tracking IDs and statuses
+    fn advisories() -> Vec<RetrievedAdvisory> {
+        let csaf: Value = serde_json::from_str(include_str!(
+            "../../../tests/data/csaf/product-coverage.json"
+        ))
+        .unwrap();
+
+        (0..16)
+            .map(|n| {
+                let mut csaf = csaf.clone();
+                csaf["document"]["tracking"]["id"] = format!("EXAMPLE-2023-{n:04}").into();
+                if n % 2 == 0 {
+                    let status = &mut csaf["vulnerabilities"][0]["product_status"];
+                    status["fixed"] = status["known_affected"].take();
+                }
+
+                RetrievedAdvisory {
+                    discovered: DiscoveredAdvisory {
+                        url: format!("file:///advisories/example-2023-{n:04}.json")
+                            .parse()
+                            .unwrap(),
+                    },
+                    data: serde_json::to_vec(&csaf).unwrap().into(),
+                    signature: None,
+                    sha256: None,
+                    sha512: None,
+                    metadata: RetrievalMetadata {
+                        last_modification: None,
+                        etag: None,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// process the advisories in the order given, verifying and filtering them
+    async fn run<T: VexTask + 'static>(
+        task: T,
+        verifier: Verifier,
+        filter: Filter,
+        advisories: Vec<RetrievedAdvisory>,
+    ) -> Processing<T> {
+        let processing = Arc::new(Processing {
+            task,
+            verifier,
+            skip_invalid: false,
+            report: Mutex::new(VexReport::default()),
+            filter,
+            filtered: AtomicUsize::new(0),
+        });
+
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let processed = tokio::spawn(process_advisories(processing.clone(), rx));
+        for advisory in advisories {
+            tx.send(advisory).await.unwrap();
+        }
+        drop(tx);
+        processed.await.unwrap().unwrap();
+
+        Arc::into_inner(processing).unwrap()
+    }
+
+    /// process the advisories in the order given
+    async fn process<T: VexTask + 'static>(task: T, advisories: Vec<RetrievedAdvisory>) -> T {
+        let verifier = Verifier::new(false, None).unwrap();
+        let processing = run(task, verifier, Filter::default(), advisories).await;
+        assert!(processing.report.into_inner().is_empty());
+        processing.task
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn deterministic() {
+        let forward = advisories();
+        let mut backward = advisories();
+        backward.reverse();
+
+        let query = || Query::parse("FROM vulnerabilities[*].product_status.fixed[*]").unwrap();
+        let mut a = process(QueryCsaf::new(query()), forward.clone()).await;
+        let mut b = process(QueryCsaf::new(query()), backward.clone()).await;
+        let (a, b) = (a.result(), b.result());
+        assert_eq!(a.count, 16);
+        assert_eq!(a.values, b.values);
+
+        let key = |task: CollectStatements| {
+            let mut statements = task
+                .statements
+                .into_inner()
+                .into_iter()
+                .map(|s| (s.advisory, s.product.product_id, s.status))
+                .collect::<Vec<_>>();
+            statements.sort();
+            statements
+        };
+        let a = key(process(CollectStatements::default(), forward).await);
+        let b = key(process(CollectStatements::default(), backward).await);
+        assert_eq!(a.len(), 32);
+        assert_eq!(a, b);
+    }
+
+    #[tokio::test]
+    async fn filter_before_verify() {
+        let timestamp = |t| Some(OffsetDateTime::from_unix_timestamp(t).unwrap());
+        let mut advisories = advisories();
+        for (n, advisory) in advisories.iter_mut().enumerate() {
+            advisory.metadata.last_modification = match n % 4 {
+                // 2023-01-01, skipped by the retrieval
+                0 => timestamp(1672531200),
+                // 2023-07-01, passing
+                1 => timestamp(1688169600),
+                // skipped by the current release date of the document
+                _ => None,
+            };
+        }
+
+        // no advisory has digests
+        let verifier = Verifier::new(true, None).unwrap();
+        let filter = Filter {
+            // 2023-06-01
+            modified_since: Some(OffsetDateTime::from_unix_timestamp(1685577600).unwrap()),
+            ..Default::default()
+        };
+        let processing = run(CollectStatements::default(), verifier, filter, advisories).await;
+
+        assert_eq!(processing.filtered.into_inner(), 12);
+        let failures = processing.report.into_inner().failures;
+        assert_eq!(failures.len(), 4);
+        assert!(failures
+            .iter()
+            .all(|f| f.kind == FailureKind::Verification("missing-digest")));
+        assert_eq!(processing.task.statements.into_inner().len(), 8);
+    }
+}
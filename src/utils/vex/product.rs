@@ -1,49 +1,166 @@
 use csaf::{
-    definitions::{Branch, BranchesT},
+    definitions::{
+        Branch, BranchesT, FullProductName, ProductGroupIdT, ProductIdT,
+        ProductIdentificationHelper,
+    },
     product_tree::Relationship,
     Csaf,
 };
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
-pub fn collect_cpe<'a>(csaf: &'a Csaf) -> BTreeSet<String> {
-    let mut result = BTreeSet::new();
+/// An index of the product tree of a document, mapping product IDs to their definitions.
+///
+/// Products are taken from the branches, the `full_product_names` and the relationships of the
+/// product tree. Building it walks the tree once, so that it should be preferred over the free
+/// functions when looking up more than a single product.
+#[derive(Clone, Debug, Default)]
+pub struct ProductIndex<'a> {
+    traces: BTreeMap<&'a str, Vec<&'a Branch>>,
+    products: BTreeMap<&'a str, &'a FullProductName>,
+    relationships: BTreeMap<&'a str, Vec<&'a Relationship>>,
+    groups: BTreeMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> ProductIndex<'a> {
+    pub fn new(csaf: &'a Csaf) -> Self {
+        let mut result = Self::default();
+
+        let Some(product_tree) = &csaf.product_tree else {
+            return result;
+        };
 
-    if let Some(product_tree) = &csaf.product_tree {
-        walk_product_branches(&product_tree.branches, |_parents, branch| {
+        walk_product_branches(&product_tree.branches, |parents, branch| {
             if let Some(product) = &branch.product {
-                if let Some(id) = &product.product_identification_helper {
-                    if let Some(cpe) = &id.cpe {
-                        result.insert(cpe.to_string());
-                    }
-                }
+                let id = product.product_id.0.as_str();
+                result.products.insert(id, product);
+                result
+                    .traces
+                    .insert(id, parents.iter().copied().chain(Some(branch)).collect());
             }
         });
+
+        for product in product_tree.full_product_names.iter().flatten() {
+            result
+                .products
+                .insert(product.product_id.0.as_str(), product);
+        }
+
+        for rel in product_tree.relationships.iter().flatten() {
+            let product = &rel.full_product_name;
+            result
+                .products
+                .insert(product.product_id.0.as_str(), product);
+            result
+                .relationships
+                .entry(rel.full_product_name.product_id.0.as_str())
+                .or_default()
+                .push(rel);
+        }
+
+        for group in product_tree.product_groups.iter().flatten() {
+            result
+                .groups
+                .entry(group.group_id.0.as_str())
+                .or_default()
+                .extend(group.product_ids.iter().map(|id| id.0.as_str()));
+        }
+
+        result
     }
 
-    result
-}
+    /// The chain of branches from the root down to the product, empty if it isn't defined in a branch
+    pub fn trace(&self, product_id: &str) -> &[&'a Branch] {
+        self.traces
+            .get(product_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
 
-/// build the chain form a product ID up to the parent
-pub fn trace_product<'a>(csaf: &'a Csaf, product_id: &str) -> Vec<&'a Branch> {
-    let mut result = vec![];
+    pub fn product(&self, product_id: &str) -> Option<&'a FullProductName> {
+        self.products.get(product_id).copied()
+    }
 
-    if let Some(product_tree) = &csaf.product_tree {
-        // let result = &mut result;
-        walk_product_branches(&product_tree.branches, |parents, branch| {
-            if let Some(full_name) = &branch.product {
-                if full_name.product_id.0 == product_id {
-                    // trace back
-                    result = parents
-                        .iter()
-                        .copied()
-                        .chain(Some(branch))
-                        .collect::<Vec<&'a Branch>>()
-                }
-            }
-        });
+    pub fn helper(&self, product_id: &str) -> Option<&'a ProductIdentificationHelper> {
+        self.product(product_id)?
+            .product_identification_helper
+            .as_ref()
+    }
+
+    /// The relationships defining the product
+    pub fn relations(&self, product_id: &str) -> &[&'a Relationship] {
+        self.relationships
+            .get(product_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
     }
 
-    result
+    /// The product IDs of a product group
+    pub fn group(&self, group_id: &str) -> &[&'a str] {
+        self.groups
+            .get(group_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Check if a product is part of the product IDs, or of one of the groups
+    pub fn refers_to(
+        &self,
+        product_ids: &Option<Vec<ProductIdT>>,
+        group_ids: &Option<Vec<ProductGroupIdT>>,
+        product_id: &str,
+    ) -> bool {
+        product_ids.iter().flatten().any(|id| id.0 == product_id)
+            || group_ids
+                .iter()
+                .flatten()
+                .any(|g| self.group(&g.0).contains(&product_id))
+    }
+
+    /// Check if the product is defined in a branch, like the free [`has_product`].
+    ///
+    /// Products defined by a full product name or a relationship can be looked up with
+    /// [`Self::product`].
+    pub fn has_product(&self, product_id: &str) -> bool {
+        self.traces.contains_key(product_id)
+    }
+
+    /// All products, ordered by their ID
+    pub fn products(&self) -> impl Iterator<Item = &'a FullProductName> + '_ {
+        self.products.values().copied()
+    }
+}
+
+/// collect a field of the identification helpers of all products
+fn collect_helper<T: ToString>(
+    csaf: &Csaf,
+    f: impl Fn(&ProductIdentificationHelper) -> Option<&T>,
+) -> BTreeSet<String> {
+    ProductIndex::new(csaf)
+        .products()
+        .filter_map(|product| f(product.product_identification_helper.as_ref()?))
+        .map(|value| value.to_string())
+        .collect()
+}
+
+pub fn collect_cpe(csaf: &Csaf) -> BTreeSet<String> {
+    collect_helper(csaf, |helper| helper.cpe.as_ref())
+}
+
+pub fn collect_purl(csaf: &Csaf) -> BTreeSet<String> {
+    collect_helper(csaf, |helper| helper.purl.as_ref())
+}
+
+/// collect all products defined in the product tree
+pub fn all_products(csaf: &Csaf) -> Vec<&FullProductName> {
+    ProductIndex::new(csaf).products().collect()
+}
+
+/// build the chain form a product ID up to the parent
+///
+/// Like all free functions below, this builds a [`ProductIndex`] for a single lookup. Build the
+/// index once instead, when looking up more than one product.
+pub fn trace_product<'a>(csaf: &'a Csaf, product_id: &str) -> Vec<&'a Branch> {
+    ProductIndex::new(csaf).trace(product_id).to_vec()
 }
 
 pub fn walk_product_branches<'a, F>(branches: &'a Option<BranchesT>, mut f: F)
@@ -71,9 +188,9 @@ fn walk_product_branches_ref<'a, F>(
     }
 }
 
-/// check if we have a product in our product tree
+/// check if we have a product in our product tree, see [`ProductIndex::has_product`]
 pub fn has_product(csaf: &Csaf, product_id: &str) -> bool {
-    !trace_product(csaf, product_id).is_empty()
+    ProductIndex::new(csaf).has_product(product_id)
 }
 
 /// find relations to a product id
@@ -81,9 +198,13 @@ pub fn find_product_relations<'a>(
     csaf: &'a Csaf,
     product: &'a str,
 ) -> impl Iterator<Item = &'a Relationship> + 'a {
-    csaf.product_tree
-        .iter()
-        .flat_map(|pt| pt.relationships.iter())
-        .flat_map(|r| r.iter())
-        .filter(move |p| p.full_product_name.product_id.0 == product)
+    ProductIndex::new(csaf)
+        .relations(product)
+        .to_vec()
+        .into_iter()
+}
+
+/// the product IDs of a product group
+pub fn group_products<'a>(csaf: &'a Csaf, group_id: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    ProductIndex::new(csaf).group(group_id).to_vec().into_iter()
 }
@@ -0,0 +1,139 @@
+//! Find the SBOMs affected by a CVE.
+
+use crate::tasks::Task;
+use crate::utils::vex::identify::{ProductRef, SbomIndex};
+use crate::utils::vex::product::ProductIndex;
+use crate::utils::vex::status::{vulnerability_status, Status};
+use crate::utils::vex::{ParsedAdvisory, VexTask};
+use async_trait::async_trait;
+use csaf::Csaf;
+use indicatif::ProgressBar;
+use spdx_rs::models::SPDX;
+use std::collections::BTreeSet;
+use tokio::sync::Mutex;
+
+/// A product mentioned for a CVE
+#[derive(Clone, Debug)]
+pub struct CveProduct {
+    /// The tracking ID of the advisory
+    pub advisory: String,
+    pub status: Status,
+    pub product: ProductRef,
+}
+
+/// Collect the advisories and products mentioning a CVE
+pub struct FindCve {
+    pub cve: String,
+    pub advisories: Mutex<BTreeSet<String>>,
+    pub products: Mutex<Vec<CveProduct>>,
+}
+
+impl FindCve {
+    pub fn new(cve: impl Into<String>) -> Self {
+        Self {
+            cve: cve.into(),
+            advisories: Default::default(),
+            products: Default::default(),
+        }
+    }
+}
+
+/// the products of an advisory mentioned for a CVE, `None` if the advisory doesn't mention it
+fn cve_products(csaf: &Csaf, cve: &str) -> Option<Vec<CveProduct>> {
+    let index = ProductIndex::new(csaf);
+    let mut result = None;
+
+    for vulnerability in csaf.vulnerabilities.iter().flatten() {
+        if !vulnerability
+            .cve
+            .as_deref()
+            .is_some_and(|id| id.eq_ignore_ascii_case(cve))
+        {
+            continue;
+        }
+
+        let products = result.get_or_insert_with(Vec::new);
+        for (product_id, status) in vulnerability_status(vulnerability) {
+            products.push(CveProduct {
+                advisory: csaf.document.tracking.id.clone(),
+                status,
+                product: ProductRef::resolve(&index, product_id),
+            });
+        }
+    }
+
+    result
+}
+
+#[async_trait]
+impl VexTask for FindCve {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        // resolving all products is CPU bound
+        let cve = self.cve.clone();
+        let Some((id, products)) = tokio::task::spawn_blocking(move || {
+            let products = cve_products(&advisory.csaf, &cve)?;
+            Some((advisory.csaf.document.tracking.id, products))
+        })
+        .await?
+        else {
+            return Ok(());
+        };
+
+        self.advisories.lock().await.insert(id);
+        self.products.lock().await.extend(products);
+
+        Ok(())
+    }
+}
+
+/// An SBOM, affected by a product of a CVE
+#[derive(Clone, Debug)]
+pub struct AffectedSbom {
+    pub sbom: String,
+    pub advisory: String,
+    pub product_id: String,
+    pub status: Status,
+    /// The names of the matching packages
+    pub packages: BTreeSet<String>,
+}
+
+/// Find the SBOMs matching the products of a CVE
+pub struct AffectedSboms {
+    pub products: Vec<CveProduct>,
+    pub sboms: Vec<AffectedSbom>,
+}
+
+impl AffectedSboms {
+    pub fn new(products: Vec<CveProduct>) -> Self {
+        Self {
+            products,
+            sboms: vec![],
+        }
+    }
+}
+
+impl Task for AffectedSboms {
+    fn process(&mut self, _progress: &ProgressBar, spdx: &SPDX) -> anyhow::Result<()> {
+        let sbom = SbomIndex::new(spdx);
+
+        for product in &self.products {
+            let matches = product.product.match_sbom(&sbom);
+            if matches.is_empty() {
+                continue;
+            }
+
+            self.sboms.push(AffectedSbom {
+                sbom: spdx.document_creation_information.document_name.clone(),
+                advisory: product.advisory.clone(),
+                product_id: product.product.product_id.clone(),
+                status: product.status,
+                packages: matches
+                    .into_iter()
+                    .map(|m| m.package.package_name.clone())
+                    .collect(),
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+//! Report the vulnerabilities of an SBOM.
+
+use crate::tasks::Task;
+use crate::utils::vex::identify::{ProductRef, SbomIndex};
+use crate::utils::vex::product::ProductIndex;
+use crate::utils::vex::status::{vulnerability_status, Status};
+use crate::utils::vex::{schema_name, ParsedAdvisory, VexTask};
+use async_trait::async_trait;
+use csaf::Csaf;
+use indicatif::ProgressBar;
+use spdx_rs::models::SPDX;
+use std::collections::BTreeSet;
+use tokio::sync::Mutex;
+
+#[derive(Clone, Debug)]
+pub struct Remediation {
+    pub category: String,
+    pub details: String,
+    pub url: Option<String>,
+}
+
+/// The status of a product for a vulnerability
+#[derive(Clone, Debug)]
+pub struct Statement {
+    pub cve: String,
+    /// The tracking ID of the advisory
+    pub advisory: String,
+    pub status: Status,
+    /// The component and platforms of the product
+    pub product: ProductRef,
+    /// Labels of flags applying to the product, justifying a `not_affected` status
+    pub flags: BTreeSet<String>,
+    /// Details of impact threats applying to the product
+    pub impacts: Vec<String>,
+    pub remediations: Vec<Remediation>,
+    /// The product IDs with the status `fixed`, when the product is affected.
+    ///
+    /// Only products sharing the component or a platform with the affected product are listed.
+    pub fixed: Vec<String>,
+}
+
+/// Collect all statements of the VEX corpus
+#[derive(Default)]
+pub struct CollectStatements {
+    pub statements: Mutex<Vec<Statement>>,
+}
+
+/// check if two products share the component or a platform
+fn related(a: &ProductRef, b: &ProductRef) -> bool {
+    a.component.product_id == b.component.product_id
+        || a.platforms.iter().any(|p| {
+            b.platforms
+                .iter()
+                .any(|q| p.product.product_id == q.product.product_id)
+        })
+}
+
+/// the statements of all products of an advisory
+fn statements(csaf: &Csaf) -> Vec<Statement> {
+    let index = ProductIndex::new(csaf);
+    let mut statements = vec![];
+
+    for vulnerability in csaf.vulnerabilities.iter().flatten() {
+        let Some(cve) = &vulnerability.cve else {
+            continue;
+        };
+
+        let status = vulnerability_status(vulnerability)
+            .into_iter()
+            .map(|(product_id, status)| (status, ProductRef::resolve(&index, product_id)))
+            .collect::<Vec<_>>();
+        let fixed = status
+            .iter()
+            .filter(|(status, _)| *status == Status::Fixed)
+            .map(|(_, product)| product)
+            .collect::<Vec<_>>();
+
+        for (status, product) in &status {
+            let status = *status;
+            let product_id = product.product_id.as_str();
+            let flags = vulnerability
+                .flags
+                .iter()
+                .flatten()
+                .filter(|f| index.refers_to(&f.product_ids, &f.group_ids, product_id))
+                .map(|f| schema_name(&f.label))
+                .collect();
+
+            let impacts = vulnerability
+                .threats
+                .iter()
+                .flatten()
+                .filter(|t| schema_name(&t.category) == "impact")
+                .filter(|t| index.refers_to(&t.product_ids, &t.group_ids, product_id))
+                .map(|t| t.details.clone())
+                .collect();
+
+            let remediations = vulnerability
+                .remediations
+                .iter()
+                .flatten()
+                .filter(|r| index.refers_to(&r.product_ids, &r.group_ids, product_id))
+                .map(|r| Remediation {
+                    category: schema_name(&r.category),
+                    details: r.details.clone(),
+                    url: r.url.as_ref().map(|url| url.to_string()),
+                })
+                .collect();
+
+            statements.push(Statement {
+                cve: cve.clone(),
+                advisory: csaf.document.tracking.id.clone(),
+                status,
+                product: product.clone(),
+                flags,
+                impacts,
+                remediations,
+                fixed: match status {
+                    Status::Affected => fixed
+                        .iter()
+                        .filter(|fixed| related(product, fixed))
+                        .map(|fixed| fixed.product_id.clone())
+                        .collect(),
+                    _ => vec![],
+                },
+            });
+        }
+    }
+
+    statements
+}
+
+#[async_trait]
+impl VexTask for CollectStatements {
+    async fn process(&self, advisory: ParsedAdvisory) -> anyhow::Result<()> {
+        // resolving all products is CPU bound
+        let statements = tokio::task::spawn_blocking(move || statements(&advisory.csaf)).await?;
+
+        self.statements.lock().await.extend(statements);
+
+        Ok(())
+    }
+}
+
+/// A statement, applying to packages of the SBOM
+#[derive(Clone, Debug)]
+pub struct Finding<'a> {
+    pub statement: &'a Statement,
+    /// Package names, and if they are a main package
+    pub packages: BTreeSet<(String, bool)>,
+}
+
+/// Report the statements applying to a single SBOM
+pub struct SbomReport<'a> {
+    /// The name of the SBOM document
+    pub name: String,
+    pub statements: &'a [Statement],
+    pub findings: Vec<Finding<'a>>,
+    pub found: bool,
+}
+
+impl<'a> SbomReport<'a> {
+    pub fn new(name: impl Into<String>, statements: &'a [Statement]) -> Self {
+        Self {
+            name: name.into(),
+            statements,
+            findings: vec![],
+            found: false,
+        }
+    }
+}
+
+impl Task for SbomReport<'_> {
+    fn process(&mut self, _progress: &ProgressBar, spdx: &SPDX) -> anyhow::Result<()> {
+        let info = &spdx.document_creation_information;
+        if info.document_name != self.name {
+            return Ok(());
+        }
+        self.found = true;
+        let sbom = SbomIndex::new(spdx);
+
+        for statement in self.statements {
+            let packages = statement
+                .product
+                .match_sbom(&sbom)
+                .into_iter()
+                .map(|m| (m.package.package_name.clone(), m.main))
+                .collect::<BTreeSet<_>>();
+
+            if !packages.is_empty() {
+                self.findings.push(Finding {
+                    statement,
+                    packages,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+//! Describe the products of a product tree, by the branches they are defined in.
+
+use crate::utils::purl::Purl;
+use crate::utils::vex::identify::Helpers;
+use crate::utils::vex::product::ProductIndex;
+use crate::utils::vex::schema_name;
+use std::fmt::{Display, Formatter};
+
+/// A product defined in the branches of the product tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Product {
+    pub product_id: String,
+    /// The name of the `product_name` branch, or the full product name
+    pub name: String,
+    /// The name of the `product_version` branch, or the version of the purl
+    pub version: Option<String>,
+    pub cpe: Option<String>,
+    pub purl: Option<Purl>,
+    /// All identification helpers, for matching the product
+    pub helpers: Helpers,
+}
+
+impl Product {
+    /// Describe a product, which is only named by its ID if it isn't defined in the product tree
+    pub fn from_index(index: &ProductIndex, product_id: &str) -> Self {
+        let Some(product) = index.product(product_id) else {
+            return Self {
+                product_id: product_id.to_string(),
+                name: product_id.to_string(),
+                version: None,
+                cpe: None,
+                purl: None,
+                helpers: Helpers::default(),
+            };
+        };
+        let trace = index.trace(product_id);
+
+        let branch = |category: &str| {
+            trace
+                .iter()
+                .rev()
+                .find(|branch| schema_name(&branch.category) == category)
+                .map(|branch| branch.name.clone())
+        };
+
+        let helper = product.product_identification_helper.as_ref();
+        let cpe = helper.and_then(|h| h.cpe.clone());
+        let purl = helper
+            .and_then(|h| h.purl.as_ref())
+            .and_then(|purl| Purl::parse(purl.as_str()).ok());
+
+        Self {
+            product_id: product_id.to_string(),
+            name: branch("product_name").unwrap_or_else(|| product.name.clone()),
+            version: branch("product_version")
+                .or_else(|| purl.as_ref().and_then(|purl| purl.version.clone())),
+            cpe,
+            purl,
+            helpers: Helpers::from_product(product),
+        }
+    }
+}
+
+impl Display for Product {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(version) = &self.version {
+            write!(f, " {version}")?;
+        }
+        if let Some(cpe) = &self.cpe {
+            write!(f, " ({cpe})")?;
+        } else if let Some(purl) = &self.purl {
+            write!(f, " ({purl})")?;
+        }
+        Ok(())
+    }
+}
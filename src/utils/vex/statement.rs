@@ -0,0 +1,299 @@
+//! A common model of VEX statements, for CSAF, OpenVEX and CycloneDX documents.
+
+use crate::utils::vex::identify::ProductRef;
+use crate::utils::vex::product::ProductIndex;
+use crate::utils::vex::schema_name;
+use crate::utils::vex::status::{vulnerability_status, Status};
+use anyhow::{anyhow, bail};
+use csaf::Csaf;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Format {
+    Csaf,
+    OpenVex,
+    CycloneDx,
+}
+
+impl Format {
+    /// Detect the format of a JSON document
+    pub fn detect(value: &Value) -> Option<Self> {
+        if value["bomFormat"].as_str() == Some("CycloneDX") {
+            Some(Self::CycloneDx)
+        } else if value["@context"]
+            .as_str()
+            .is_some_and(|context| context.starts_with("https://openvex.dev/"))
+        {
+            Some(Self::OpenVex)
+        } else if value["document"]["csaf_version"].is_string() {
+            Some(Self::Csaf)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Csaf => "CSAF",
+            Self::OpenVex => "OpenVEX",
+            Self::CycloneDx => "CycloneDX",
+        })
+    }
+}
+
+/// How a product is identified by a statement
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProductIdentifier {
+    Purl(String),
+    Cpe(String),
+    /// An identifier local to the document, like a CSAF product ID or a CycloneDX BOM reference
+    Id(String),
+}
+
+impl ProductIdentifier {
+    /// Detect the kind of identifier from its value
+    pub fn detect(value: &str) -> Self {
+        if value.starts_with("pkg:") {
+            Self::Purl(value.to_string())
+        } else if value.starts_with("cpe:") {
+            Self::Cpe(value.to_string())
+        } else {
+            Self::Id(value.to_string())
+        }
+    }
+}
+
+impl Display for ProductIdentifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Purl(value) | Self::Cpe(value) | Self::Id(value) => f.write_str(value),
+        }
+    }
+}
+
+/// The status of a product for a vulnerability
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    pub vulnerability: String,
+    /// The component the status is about
+    pub product: ProductIdentifier,
+    pub status: Status,
+    /// The justification for a `not_affected` status, as named by the format
+    pub justification: Option<String>,
+    /// The time of the statement, as RFC 3339
+    pub timestamp: Option<String>,
+}
+
+/// The statements of a VEX document
+#[derive(Clone, Debug)]
+pub struct VexDocument {
+    pub format: Format,
+    pub id: String,
+    pub statements: Vec<Statement>,
+}
+
+impl VexDocument {
+    /// Parse a JSON document of any supported format
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let value: Value = serde_json::from_slice(data)?;
+
+        match Format::detect(&value) {
+            Some(Format::Csaf) => Ok(Self::from_csaf(&serde_json::from_value(value)?)),
+            Some(Format::OpenVex) => Self::from_openvex(&value),
+            Some(Format::CycloneDx) => Self::from_cyclonedx(&value),
+            None => bail!("Unknown VEX format"),
+        }
+    }
+
+    /// Convert a CSAF document, identifying products by the purl or CPE of their component
+    pub fn from_csaf(csaf: &Csaf) -> Self {
+        let index = ProductIndex::new(csaf);
+        let tracking = &csaf.document.tracking;
+        let released = schema_name(&tracking.current_release_date);
+
+        let mut statements = vec![];
+
+        for vulnerability in csaf.vulnerabilities.iter().flatten() {
+            let Some(cve) = &vulnerability.cve else {
+                continue;
+            };
+
+            for (product_id, status) in vulnerability_status(vulnerability) {
+                let component = ProductRef::resolve(&index, product_id).component;
+                let product = component
+                    .purl
+                    .map(|purl| ProductIdentifier::Purl(purl.to_string()))
+                    .or(component.cpe.map(ProductIdentifier::Cpe))
+                    .unwrap_or_else(|| ProductIdentifier::Id(product_id.to_string()));
+
+                let flag = vulnerability
+                    .flags
+                    .iter()
+                    .flatten()
+                    .find(|f| index.refers_to(&f.product_ids, &f.group_ids, product_id));
+
+                statements.push(Statement {
+                    vulnerability: cve.clone(),
+                    product,
+                    status,
+                    justification: flag.map(|f| schema_name(&f.label)),
+                    timestamp: Some(
+                        flag.and_then(|f| f.date.as_ref())
+                            .map(schema_name)
+                            .unwrap_or_else(|| released.clone()),
+                    ),
+                });
+            }
+        }
+
+        Self {
+            format: Format::Csaf,
+            id: tracking.id.clone(),
+            statements,
+        }
+    }
+
+    /// Convert an OpenVEX document, identifying products by their subcomponents, if present.
+    pub fn from_openvex(value: &Value) -> anyhow::Result<Self> {
+        let id = value["@id"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing document ID"))?;
+        let timestamp = value["timestamp"].as_str();
+
+        let mut statements = vec![];
+
+        for statement in value["statements"].as_array().into_iter().flatten() {
+            // older versions use a plain string
+            let vulnerability = statement["vulnerability"]["name"]
+                .as_str()
+                .or(statement["vulnerability"].as_str())
+                .ok_or_else(|| anyhow!("Missing vulnerability name"))?;
+
+            let status = match statement["status"].as_str() {
+                Some("affected") => Status::Affected,
+                Some("fixed") => Status::Fixed,
+                Some("not_affected") => Status::NotAffected,
+                Some("under_investigation") => Status::UnderInvestigation,
+                other => bail!("Invalid status: {other:?}"),
+            };
+
+            let justification = statement["justification"].as_str().map(|j| j.to_string());
+            let timestamp = statement["timestamp"]
+                .as_str()
+                .or(timestamp)
+                .map(|t| t.to_string());
+
+            for product in statement["products"].as_array().into_iter().flatten() {
+                let subcomponents = product["subcomponents"]
+                    .as_array()
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.iter().collect::<Vec<_>>())
+                    .unwrap_or_else(|| vec![product]);
+
+                for component in subcomponents {
+                    let Some(id) = component["@id"].as_str().or(component.as_str()) else {
+                        continue;
+                    };
+                    statements.push(Statement {
+                        vulnerability: vulnerability.to_string(),
+                        product: ProductIdentifier::detect(id),
+                        status,
+                        justification: justification.clone(),
+                        timestamp: timestamp.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            format: Format::OpenVex,
+            id: id.to_string(),
+            statements,
+        })
+    }
+
+    /// Convert a CycloneDX document, resolving references to the purl or CPE of the component.
+    pub fn from_cyclonedx(value: &Value) -> anyhow::Result<Self> {
+        let id = value["serialNumber"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing serial number"))?;
+        let timestamp = value["metadata"]["timestamp"].as_str();
+
+        let mut components = BTreeMap::new();
+        collect_components(&value["metadata"]["component"], &mut components);
+        for component in value["components"].as_array().into_iter().flatten() {
+            collect_components(component, &mut components);
+        }
+
+        let mut statements = vec![];
+
+        for vulnerability in value["vulnerabilities"].as_array().into_iter().flatten() {
+            let name = vulnerability["id"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Missing vulnerability ID"))?;
+            let analysis = &vulnerability["analysis"];
+
+            // a vulnerability without an analysis affects the components
+            let status = match analysis["state"].as_str() {
+                None | Some("exploitable") => Status::Affected,
+                Some("resolved") | Some("resolved_with_pedigree") => Status::Fixed,
+                Some("not_affected") | Some("false_positive") => Status::NotAffected,
+                Some("in_triage") => Status::UnderInvestigation,
+                Some(other) => bail!("Invalid analysis state: {other}"),
+            };
+
+            let justification = analysis["justification"].as_str().map(|j| j.to_string());
+            let timestamp = analysis["lastUpdated"]
+                .as_str()
+                .or(vulnerability["updated"].as_str())
+                .or(vulnerability["published"].as_str())
+                .or(timestamp)
+                .map(|t| t.to_string());
+
+            for affects in vulnerability["affects"].as_array().into_iter().flatten() {
+                let Some(reference) = affects["ref"].as_str() else {
+                    continue;
+                };
+                statements.push(Statement {
+                    vulnerability: name.to_string(),
+                    product: components
+                        .get(reference)
+                        .cloned()
+                        .unwrap_or_else(|| ProductIdentifier::detect(reference)),
+                    status,
+                    justification: justification.clone(),
+                    timestamp: timestamp.clone(),
+                });
+            }
+        }
+
+        Ok(Self {
+            format: Format::CycloneDx,
+            id: id.to_string(),
+            statements,
+        })
+    }
+}
+
+/// map the BOM references of components, and their nested components, to their identifiers
+fn collect_components(component: &Value, result: &mut BTreeMap<String, ProductIdentifier>) {
+    if let Some(reference) = component["bom-ref"].as_str() {
+        let id = component["purl"]
+            .as_str()
+            .map(|purl| ProductIdentifier::Purl(purl.to_string()))
+            .or(component["cpe"]
+                .as_str()
+                .map(|cpe| ProductIdentifier::Cpe(cpe.to_string())));
+        if let Some(id) = id {
+            result.insert(reference.to_string(), id);
+        }
+    }
+
+    for nested in component["components"].as_array().into_iter().flatten() {
+        collect_components(nested, result);
+    }
+}
@@ -0,0 +1,55 @@
+//! Product status of vulnerabilities.
+
+use csaf::vulnerability::{ProductStatus, Vulnerability};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Status {
+    /// `known_affected`, `first_affected` and `last_affected`
+    Affected,
+    /// `fixed` and `first_fixed`
+    Fixed,
+    /// `known_not_affected`
+    NotAffected,
+    UnderInvestigation,
+    /// A recommended version, which may or may not be affected
+    Recommended,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Affected => "affected",
+            Self::Fixed => "fixed",
+            Self::NotAffected => "not_affected",
+            Self::UnderInvestigation => "under_investigation",
+            Self::Recommended => "recommended",
+        })
+    }
+}
+
+/// All product IDs of a product status, with their status
+pub fn product_status(status: &ProductStatus) -> Vec<(&str, Status)> {
+    [
+        (&status.known_affected, Status::Affected),
+        (&status.first_affected, Status::Affected),
+        (&status.last_affected, Status::Affected),
+        (&status.fixed, Status::Fixed),
+        (&status.first_fixed, Status::Fixed),
+        (&status.known_not_affected, Status::NotAffected),
+        (&status.under_investigation, Status::UnderInvestigation),
+        (&status.recommended, Status::Recommended),
+    ]
+    .into_iter()
+    .flat_map(|(ids, status)| ids.iter().flatten().map(move |id| (id.0.as_str(), status)))
+    .collect()
+}
+
+/// All product IDs of a vulnerability, with their status
+pub fn vulnerability_status(vulnerability: &Vulnerability) -> Vec<(&str, Status)> {
+    vulnerability
+        .product_status
+        .as_ref()
+        .map(product_status)
+        .unwrap_or_default()
+}
@@ -0,0 +1,489 @@
+//! Mandatory tests of CSAF 2.0 (section 6.1), as far as they can be checked on the parsed model.
+
+use crate::utils::purl::Purl;
+use crate::utils::version::compare_semver;
+use crate::utils::vex::product::{walk_product_branches, ProductIndex};
+use crate::utils::vex::schema_name;
+use crate::utils::vex::status::{vulnerability_status, Status};
+use csaf::Csaf;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// 6.1.1: a product ID is referenced, but not defined
+    MissingProductDefinition(String),
+    /// 6.1.2: a product ID is defined more than once
+    MultipleProductDefinition(String),
+    /// 6.1.3: a relationship references the product it defines, directly or through other
+    /// relationships
+    CircularProductDefinition(String),
+    /// 6.1.4: a product group ID is referenced, but not defined
+    MissingGroupDefinition(String),
+    /// 6.1.5: a product group ID is defined more than once
+    MultipleGroupDefinition(String),
+    /// 6.1.6: a product is in contradicting status groups of the same vulnerability
+    ContradictingProductStatus {
+        vulnerability: String,
+        product_id: String,
+        statuses: BTreeSet<Status>,
+    },
+    /// 6.1.7: a product has more than one score of the same CVSS version
+    MultipleScores {
+        vulnerability: String,
+        product_id: String,
+        version: String,
+    },
+    /// 6.1.10: the CVSS vector doesn't match the properties of the score
+    InconsistentCvss {
+        vulnerability: String,
+        property: &'static str,
+        vector: String,
+    },
+    /// 6.1.13: an identification helper has an invalid purl
+    InvalidPurl { purl: String, error: String },
+    /// 6.1.16: the document version isn't the latest revision
+    LatestDocumentVersion { version: String, latest: String },
+    /// 6.1.23: a CVE is used by more than one vulnerability
+    MultipleCve(String),
+}
+
+impl Violation {
+    /// The section of the mandatory test
+    pub fn test(&self) -> &'static str {
+        match self {
+            Self::MissingProductDefinition(_) => "6.1.1",
+            Self::MultipleProductDefinition(_) => "6.1.2",
+            Self::CircularProductDefinition(_) => "6.1.3",
+            Self::MissingGroupDefinition(_) => "6.1.4",
+            Self::MultipleGroupDefinition(_) => "6.1.5",
+            Self::ContradictingProductStatus { .. } => "6.1.6",
+            Self::MultipleScores { .. } => "6.1.7",
+            Self::InconsistentCvss { .. } => "6.1.10",
+            Self::InvalidPurl { .. } => "6.1.13",
+            Self::LatestDocumentVersion { .. } => "6.1.16",
+            Self::MultipleCve(_) => "6.1.23",
+        }
+    }
+
+    /// A short name for the kind of violation, suitable for aggregating
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::MissingProductDefinition(_) => "missing-product-definition",
+            Self::MultipleProductDefinition(_) => "multiple-product-definition",
+            Self::CircularProductDefinition(_) => "circular-product-definition",
+            Self::MissingGroupDefinition(_) => "missing-group-definition",
+            Self::MultipleGroupDefinition(_) => "multiple-group-definition",
+            Self::ContradictingProductStatus { .. } => "contradicting-product-status",
+            Self::MultipleScores { .. } => "multiple-scores",
+            Self::InconsistentCvss { .. } => "inconsistent-cvss",
+            Self::InvalidPurl { .. } => "invalid-purl",
+            Self::LatestDocumentVersion { .. } => "latest-document-version",
+            Self::MultipleCve(_) => "multiple-cve",
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ", self.test())?;
+        match self {
+            Self::MissingProductDefinition(id) => write!(f, "missing definition of product: {id}"),
+            Self::MultipleProductDefinition(id) => {
+                write!(f, "multiple definitions of product: {id}")
+            }
+            Self::CircularProductDefinition(id) => {
+                write!(f, "circular definition of product: {id}")
+            }
+            Self::MissingGroupDefinition(id) => write!(f, "missing definition of group: {id}"),
+            Self::MultipleGroupDefinition(id) => write!(f, "multiple definitions of group: {id}"),
+            Self::ContradictingProductStatus {
+                vulnerability,
+                product_id,
+                statuses,
+            } => {
+                let statuses = statuses
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "{vulnerability}: contradicting status of {product_id}: {statuses}"
+                )
+            }
+            Self::MultipleScores {
+                vulnerability,
+                product_id,
+                version,
+            } => write!(
+                f,
+                "{vulnerability}: multiple CVSS {version} scores for {product_id}"
+            ),
+            Self::InconsistentCvss {
+                vulnerability,
+                property,
+                vector,
+            } => write!(
+                f,
+                "{vulnerability}: {property} doesn't match vector {vector}"
+            ),
+            Self::InvalidPurl { purl, error } => write!(f, "invalid purl {purl}: {error}"),
+            Self::LatestDocumentVersion { version, latest } => {
+                write!(
+                    f,
+                    "document version {version} isn't the latest revision {latest}"
+                )
+            }
+            Self::MultipleCve(cve) => write!(f, "multiple vulnerabilities for {cve}"),
+        }
+    }
+}
+
+/// A metric of a vector string, with its property name and value names
+type Metric = (
+    &'static str,
+    &'static str,
+    &'static [(&'static str, &'static str)],
+);
+
+/// The CVSS v3 base metrics
+const CVSS_V3: &[Metric] = &[
+    (
+        "AV",
+        "attackVector",
+        &[
+            ("N", "NETWORK"),
+            ("A", "ADJACENT_NETWORK"),
+            ("L", "LOCAL"),
+            ("P", "PHYSICAL"),
+        ],
+    ),
+    ("AC", "attackComplexity", &[("L", "LOW"), ("H", "HIGH")]),
+    (
+        "PR",
+        "privilegesRequired",
+        &[("N", "NONE"), ("L", "LOW"), ("H", "HIGH")],
+    ),
+    ("UI", "userInteraction", &[("N", "NONE"), ("R", "REQUIRED")]),
+    ("S", "scope", &[("U", "UNCHANGED"), ("C", "CHANGED")]),
+    (
+        "C",
+        "confidentialityImpact",
+        &[("N", "NONE"), ("L", "LOW"), ("H", "HIGH")],
+    ),
+    (
+        "I",
+        "integrityImpact",
+        &[("N", "NONE"), ("L", "LOW"), ("H", "HIGH")],
+    ),
+    (
+        "A",
+        "availabilityImpact",
+        &[("N", "NONE"), ("L", "LOW"), ("H", "HIGH")],
+    ),
+];
+
+/// The CVSS v2 base metrics
+const CVSS_V2: &[Metric] = &[
+    (
+        "AV",
+        "accessVector",
+        &[("L", "LOCAL"), ("A", "ADJACENT_NETWORK"), ("N", "NETWORK")],
+    ),
+    (
+        "AC",
+        "accessComplexity",
+        &[("H", "HIGH"), ("M", "MEDIUM"), ("L", "LOW")],
+    ),
+    (
+        "Au",
+        "authentication",
+        &[("M", "MULTIPLE"), ("S", "SINGLE"), ("N", "NONE")],
+    ),
+    (
+        "C",
+        "confidentialityImpact",
+        &[("N", "NONE"), ("P", "PARTIAL"), ("C", "COMPLETE")],
+    ),
+    (
+        "I",
+        "integrityImpact",
+        &[("N", "NONE"), ("P", "PARTIAL"), ("C", "COMPLETE")],
+    ),
+    (
+        "A",
+        "availabilityImpact",
+        &[("N", "NONE"), ("P", "PARTIAL"), ("C", "COMPLETE")],
+    ),
+];
+
+/// The properties of a CVSS score, which don't match its vector string.
+///
+/// Only v3 vectors start with the version (`CVSS:3.1/`), v2 vectors are just the metrics.
+fn inconsistent_cvss(
+    cvss: &Value,
+    vector: &str,
+    definitions: &[Metric],
+    prefixed: bool,
+) -> Vec<&'static str> {
+    let mut result = vec![];
+
+    let mut metrics = vector.split('/');
+    if prefixed {
+        if let (Some(prefix), Some(version)) = (metrics.next(), cvss["version"].as_str()) {
+            if prefix.strip_prefix("CVSS:") != Some(version) {
+                result.push("version");
+            }
+        }
+    }
+    let metrics = metrics
+        .filter_map(|m| m.split_once(':'))
+        .collect::<BTreeMap<_, _>>();
+
+    for (metric, property, values) in definitions {
+        let Some(actual) = cvss[property].as_str() else {
+            continue;
+        };
+        let expected = metrics
+            .get(metric)
+            .and_then(|v| values.iter().find(|(short, _)| short == v))
+            .map(|(_, long)| *long);
+        if expected != Some(actual) {
+            result.push(*property);
+        }
+    }
+
+    result
+}
+
+/// Check if a product is defined by relationships referring back to it, directly or through the
+/// relationships defining the referenced products
+fn is_circular(index: &ProductIndex, product_id: &str) -> bool {
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![product_id];
+
+    while let Some(id) = pending.pop() {
+        for rel in index.relations(id) {
+            for next in [
+                &rel.product_reference.0,
+                &rel.relates_to_product_reference.0,
+            ] {
+                if next == product_id {
+                    return true;
+                }
+                if visited.insert(next.as_str()) {
+                    pending.push(next);
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// Run the mandatory tests on a document
+pub fn validate(csaf: &Csaf) -> Vec<Violation> {
+    let mut result = vec![];
+
+    // definitions
+
+    let mut products = BTreeMap::<&str, usize>::new();
+    let mut groups = BTreeMap::<&str, usize>::new();
+    let mut purls = vec![];
+
+    // referenced products and groups
+    let mut product_refs = BTreeSet::<&str>::new();
+    let mut group_refs = BTreeSet::<&str>::new();
+
+    if let Some(product_tree) = &csaf.product_tree {
+        let mut names = vec![];
+        walk_product_branches(&product_tree.branches, |_parents, branch| {
+            names.extend(&branch.product);
+        });
+        names.extend(product_tree.full_product_names.iter().flatten());
+        names.extend(
+            product_tree
+                .relationships
+                .iter()
+                .flatten()
+                .map(|rel| &rel.full_product_name),
+        );
+
+        for name in names {
+            *products.entry(&name.product_id.0).or_default() += 1;
+            if let Some(purl) = name
+                .product_identification_helper
+                .as_ref()
+                .and_then(|helper| helper.purl.as_ref())
+            {
+                purls.push(purl.to_string());
+            }
+        }
+
+        let index = ProductIndex::new(csaf);
+        let mut checked = BTreeSet::new();
+        for rel in product_tree.relationships.iter().flatten() {
+            let id = &rel.full_product_name.product_id.0;
+            if checked.insert(id.as_str()) && is_circular(&index, id) {
+                result.push(Violation::CircularProductDefinition(id.clone()));
+            }
+            product_refs.insert(&rel.product_reference.0);
+            product_refs.insert(&rel.relates_to_product_reference.0);
+        }
+
+        for group in product_tree.product_groups.iter().flatten() {
+            *groups.entry(&group.group_id.0).or_default() += 1;
+            product_refs.extend(group.product_ids.iter().map(|id| id.0.as_str()));
+        }
+    }
+
+    // vulnerabilities
+
+    let mut cves = BTreeMap::<&str, usize>::new();
+
+    for (n, vulnerability) in csaf.vulnerabilities.iter().flatten().enumerate() {
+        let name = vulnerability
+            .cve
+            .clone()
+            .unwrap_or_else(|| format!("vulnerabilities[{n}]"));
+        if let Some(cve) = &vulnerability.cve {
+            *cves.entry(cve).or_default() += 1;
+        }
+
+        let mut statuses = BTreeMap::<&str, BTreeSet<Status>>::new();
+        for (id, status) in vulnerability_status(vulnerability) {
+            product_refs.insert(id);
+            if status != Status::Recommended {
+                statuses.entry(id).or_default().insert(status);
+            }
+        }
+        for (id, statuses) in statuses {
+            if statuses.len() > 1 {
+                result.push(Violation::ContradictingProductStatus {
+                    vulnerability: name.clone(),
+                    product_id: id.to_string(),
+                    statuses,
+                });
+            }
+        }
+
+        for flag in vulnerability.flags.iter().flatten() {
+            product_refs.extend(flag.product_ids.iter().flatten().map(|id| id.0.as_str()));
+            group_refs.extend(flag.group_ids.iter().flatten().map(|id| id.0.as_str()));
+        }
+        for remediation in vulnerability.remediations.iter().flatten() {
+            product_refs.extend(
+                remediation
+                    .product_ids
+                    .iter()
+                    .flatten()
+                    .map(|id| id.0.as_str()),
+            );
+            group_refs.extend(
+                remediation
+                    .group_ids
+                    .iter()
+                    .flatten()
+                    .map(|id| id.0.as_str()),
+            );
+        }
+        for threat in vulnerability.threats.iter().flatten() {
+            product_refs.extend(threat.product_ids.iter().flatten().map(|id| id.0.as_str()));
+            group_refs.extend(threat.group_ids.iter().flatten().map(|id| id.0.as_str()));
+        }
+
+        let mut versions = BTreeSet::<(&str, String)>::new();
+        for score in vulnerability.scores.iter().flatten() {
+            product_refs.extend(score.products.iter().map(|id| id.0.as_str()));
+
+            // the model keeps CVSS scores as plain JSON, following the CVSS JSON schemas
+            for (cvss, definitions, prefixed) in [
+                (&score.cvss_v2, CVSS_V2, false),
+                (&score.cvss_v3, CVSS_V3, true),
+            ] {
+                let Some(cvss) = cvss else {
+                    continue;
+                };
+                let Some(version) = cvss["version"].as_str() else {
+                    continue;
+                };
+
+                for id in &score.products {
+                    if !versions.insert((&id.0, version.to_string())) {
+                        result.push(Violation::MultipleScores {
+                            vulnerability: name.clone(),
+                            product_id: id.0.clone(),
+                            version: version.to_string(),
+                        });
+                    }
+                }
+
+                if let Some(vector) = cvss["vectorString"].as_str() {
+                    for property in inconsistent_cvss(cvss, vector, definitions, prefixed) {
+                        result.push(Violation::InconsistentCvss {
+                            vulnerability: name.clone(),
+                            property,
+                            vector: vector.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // evaluate
+
+    for (id, count) in &products {
+        if *count > 1 {
+            result.push(Violation::MultipleProductDefinition(id.to_string()));
+        }
+    }
+    for id in product_refs {
+        if !products.contains_key(id) {
+            result.push(Violation::MissingProductDefinition(id.to_string()));
+        }
+    }
+    for (id, count) in &groups {
+        if *count > 1 {
+            result.push(Violation::MultipleGroupDefinition(id.to_string()));
+        }
+    }
+    for id in group_refs {
+        if !groups.contains_key(id) {
+            result.push(Violation::MissingGroupDefinition(id.to_string()));
+        }
+    }
+    for (cve, count) in cves {
+        if count > 1 {
+            result.push(Violation::MultipleCve(cve.to_string()));
+        }
+    }
+
+    for purl in purls {
+        if let Err(err) = Purl::parse(&purl) {
+            result.push(Violation::InvalidPurl {
+                purl,
+                error: err.to_string(),
+            });
+        }
+    }
+
+    let tracking = &csaf.document.tracking;
+    if schema_name(&tracking.status) != "draft" {
+        if let Some(latest) = tracking
+            .revision_history
+            .iter()
+            .map(|rev| schema_name(&rev.number))
+            .max_by(|a, b| compare_semver(a, b))
+        {
+            let version = schema_name(&tracking.version);
+            if compare_semver(&version, &latest).is_ne() {
+                result.push(Violation::LatestDocumentVersion { version, latest });
+            }
+        }
+    }
+
+    result
+}
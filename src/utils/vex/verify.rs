@@ -0,0 +1,170 @@
+//! Verify digests and OpenPGP signatures of CSAF documents.
+//!
+//! The source retrieves the `.sha256`, `.sha512` and `.asc` files along with each document,
+//! following the CSAF provider layout.
+
+use anyhow::anyhow;
+use csaf_walker::retrieve::{RetrievedAdvisory, RetrievedDigest};
+use sequoia_openpgp::cert::{Cert, CertParser};
+use sequoia_openpgp::parse::stream::{
+    DetachedVerifierBuilder, MessageLayer, MessageStructure, VerificationHelper,
+};
+use sequoia_openpgp::parse::Parse;
+use sequoia_openpgp::policy::StandardPolicy;
+use sequoia_openpgp::KeyHandle;
+use sha2::digest::{Digest, Output};
+use std::fmt::{Display, Formatter, LowerHex};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum VerificationError {
+    /// Neither a SHA-256 nor a SHA-512 digest was retrieved
+    MissingDigest,
+    DigestMismatch {
+        algorithm: &'static str,
+        expected: String,
+        actual: String,
+    },
+    /// No signature was retrieved
+    MissingSignature,
+    InvalidSignature(String),
+}
+
+impl VerificationError {
+    /// A short name for the kind of error, suitable for aggregating
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::MissingDigest => "missing-digest",
+            Self::DigestMismatch { .. } => "digest-mismatch",
+            Self::MissingSignature => "missing-signature",
+            Self::InvalidSignature(_) => "invalid-signature",
+        }
+    }
+}
+
+impl Display for VerificationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingDigest => write!(f, "missing digest"),
+            Self::DigestMismatch {
+                algorithm,
+                expected,
+                actual,
+            } => write!(f, "{algorithm} mismatch: expected {expected}, was {actual}"),
+            Self::MissingSignature => write!(f, "missing signature"),
+            Self::InvalidSignature(err) => write!(f, "invalid signature: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+/// Compare against a retrieved digest file, which has the format of `sha256sum`
+fn check_digest<D: Digest>(
+    algorithm: &'static str,
+    digest: &RetrievedDigest<D>,
+) -> Result<(), VerificationError>
+where
+    Output<D>: LowerHex,
+{
+    let expected = digest
+        .expected
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let actual = format!("{:x}", digest.actual);
+
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(VerificationError::DigestMismatch {
+            algorithm,
+            expected,
+            actual,
+        })
+    }
+}
+
+struct Helper<'a> {
+    certs: &'a [Cert],
+}
+
+impl VerificationHelper for Helper<'_> {
+    fn get_certs(&mut self, _ids: &[KeyHandle]) -> sequoia_openpgp::Result<Vec<Cert>> {
+        Ok(self.certs.to_vec())
+    }
+
+    fn check(&mut self, structure: MessageStructure) -> sequoia_openpgp::Result<()> {
+        for layer in structure {
+            if let MessageLayer::SignatureGroup { results } = layer {
+                return match results.into_iter().find(|r| r.is_ok()) {
+                    Some(_) => Ok(()),
+                    None => Err(anyhow!("no valid signature from a key of the keyring")),
+                };
+            }
+        }
+
+        Err(anyhow!("no signature"))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Verifier {
+    digests: bool,
+    /// the keyring, when verifying signatures
+    certs: Option<Vec<Cert>>,
+}
+
+impl Verifier {
+    /// Create a verifier, checking digests and/or signatures against the keys of a keyring
+    pub fn new(digests: bool, keyring: Option<&Path>) -> anyhow::Result<Self> {
+        let certs = keyring
+            .map(|keyring| {
+                CertParser::from_file(keyring)?
+                    .collect::<sequoia_openpgp::Result<Vec<_>>>()
+                    .map_err(|err| anyhow!("Failed to load keyring {}: {err}", keyring.display()))
+            })
+            .transpose()?;
+
+        Ok(Self { digests, certs })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.digests || self.certs.is_some()
+    }
+
+    /// Verify a document, using the digests and signature retrieved along with it
+    pub fn verify(&self, retrieved: &RetrievedAdvisory) -> Result<(), VerificationError> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        if self.digests {
+            if retrieved.sha256.is_none() && retrieved.sha512.is_none() {
+                return Err(VerificationError::MissingDigest);
+            }
+            if let Some(sha256) = &retrieved.sha256 {
+                check_digest("SHA-256", sha256)?;
+            }
+            if let Some(sha512) = &retrieved.sha512 {
+                check_digest("SHA-512", sha512)?;
+            }
+        }
+
+        if let Some(certs) = &self.certs {
+            let signature = retrieved
+                .signature
+                .as_ref()
+                .ok_or(VerificationError::MissingSignature)?;
+
+            let policy = StandardPolicy::new();
+            DetachedVerifierBuilder::from_bytes(signature.as_bytes())
+                .and_then(|builder| builder.with_policy(&policy, None, Helper { certs }))
+                .and_then(|mut verifier| verifier.verify_bytes(&retrieved.data))
+                .map_err(|err| VerificationError::InvalidSignature(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+}
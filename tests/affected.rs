@@ -0,0 +1,58 @@
+mod common;
+
+use common::{load_advisory, load_spdx};
+use indicatif::ProgressBar;
+use playing_with_sboms::tasks::Task;
+use playing_with_sboms::utils::vex::query::{AffectedSboms, FindCve};
+use playing_with_sboms::utils::vex::VexTask;
+use std::collections::BTreeSet;
+
+#[tokio::test]
+async fn affected() {
+    let find = FindCve::new("cve-2023-0286");
+    find.process(load_advisory()).await.unwrap();
+
+    assert_eq!(
+        find.advisories.into_inner(),
+        BTreeSet::from(["EXAMPLE-2023-0001".to_string()])
+    );
+
+    let mut affected = AffectedSboms::new(find.products.into_inner());
+    affected
+        .process(&ProgressBar::hidden(), &load_spdx())
+        .unwrap();
+
+    let mut sboms = affected
+        .sboms
+        .iter()
+        .map(|s| {
+            (
+                s.status.to_string(),
+                s.sbom.as_str(),
+                s.product_id.as_str(),
+                s.packages.iter().map(String::as_str).collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    sboms.sort();
+
+    // openssl is only affected on AppStream-8, which the appliance isn't based on
+    assert_eq!(
+        sboms,
+        vec![(
+            "affected".to_string(),
+            "appliance-1.0",
+            "appliance-1.0:firmware",
+            vec!["firmware"]
+        )]
+    );
+}
+
+#[tokio::test]
+async fn other_cve() {
+    let find = FindCve::new("CVE-2023-0001");
+    find.process(load_advisory()).await.unwrap();
+
+    assert!(find.advisories.into_inner().is_empty());
+    assert!(find.products.into_inner().is_empty());
+}
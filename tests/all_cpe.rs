@@ -0,0 +1,36 @@
+use indicatif::ProgressBar;
+use playing_with_sboms::tasks::all_cpe::AllCpe;
+use playing_with_sboms::tasks::Task;
+use spdx_rs::models::SPDX;
+use std::collections::BTreeMap;
+
+fn load_spdx() -> SPDX {
+    serde_json::from_str(include_str!("data/spdx/appliance.json")).unwrap()
+}
+
+#[test]
+fn coverage() {
+    let mut task = AllCpe::default();
+    task.process(&ProgressBar::hidden(), &load_spdx()).unwrap();
+    // processing the SBOM again counts its packages again
+    task.process(&ProgressBar::hidden(), &load_spdx()).unwrap();
+
+    // the appliance is the only main package, and carries a CPE
+    assert_eq!((task.main.packages, task.main.with_cpe), (2, 2));
+    // only the firmware has a CPE, openssl only has a purl
+    assert_eq!(
+        (task.dependencies.packages, task.dependencies.with_cpe),
+        (4, 2)
+    );
+
+    assert_eq!(
+        task.map,
+        BTreeMap::from([
+            ("cpe:/h:example:appliance:1.0".to_string(), (2, 0)),
+            (
+                "cpe:2.3:o:example:appliance_firmware:1.0:*:*:*:*:*:*:*".to_string(),
+                (0, 2)
+            ),
+        ])
+    );
+}
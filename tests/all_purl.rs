@@ -0,0 +1,25 @@
+use indicatif::ProgressBar;
+use playing_with_sboms::tasks::all_purl::AllPurl;
+use playing_with_sboms::tasks::Task;
+use spdx_rs::models::SPDX;
+use std::collections::BTreeMap;
+
+fn load_spdx() -> SPDX {
+    serde_json::from_str(include_str!("data/spdx/appliance.json")).unwrap()
+}
+
+#[test]
+fn purls() {
+    let mut task = AllPurl::default();
+    task.process(&ProgressBar::hidden(), &load_spdx()).unwrap();
+    task.process(&ProgressBar::hidden(), &load_spdx()).unwrap();
+
+    // only openssl has a purl, CPEs aren't counted
+    assert_eq!(
+        task.map,
+        BTreeMap::from([(
+            "pkg:rpm/example/openssl@1.1.1k-7.el8?arch=x86_64&epoch=1".to_string(),
+            2
+        )])
+    );
+}
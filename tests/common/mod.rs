@@ -0,0 +1,45 @@
+//! Helpers shared by the integration tests.
+
+// not every test uses every helper
+#![allow(dead_code)]
+
+use csaf_walker::discover::DiscoveredAdvisory;
+use csaf_walker::retrieve::{RetrievalMetadata, RetrievedAdvisory};
+use playing_with_sboms::utils::vex::ParsedAdvisory;
+use serde_json::Value;
+use spdx_rs::models::SPDX;
+
+pub fn load_spdx() -> SPDX {
+    serde_json::from_str(include_str!("../data/spdx/appliance.json")).unwrap()
+}
+
+pub fn load_csaf() -> Value {
+    serde_json::from_str(include_str!("../data/csaf/product-coverage.json")).unwrap()
+}
+
+/// An advisory, as if it was retrieved without any digests or signature
+pub fn advisory(csaf: Value) -> ParsedAdvisory {
+    ParsedAdvisory {
+        retrieved: RetrievedAdvisory {
+            discovered: DiscoveredAdvisory {
+                url: "https://example.com/advisories/product-coverage.json"
+                    .parse()
+                    .unwrap(),
+            },
+            data: serde_json::to_vec(&csaf).unwrap().into(),
+            signature: None,
+            sha256: None,
+            sha512: None,
+            metadata: RetrievalMetadata {
+                last_modification: None,
+                etag: None,
+            },
+        },
+        csaf: serde_json::from_value(csaf).unwrap(),
+    }
+}
+
+/// The test advisory
+pub fn load_advisory() -> ParsedAdvisory {
+    advisory(load_csaf())
+}
@@ -0,0 +1,138 @@
+use csaf::Csaf;
+use indicatif::ProgressBar;
+use playing_with_sboms::tasks::Task;
+use playing_with_sboms::utils::correlate::{
+    fixup_cpe, resolve_cpes, resolve_purls, Correlate, VexProduct,
+};
+use playing_with_sboms::utils::vex::identify::{Evidence, Helpers};
+use playing_with_sboms::utils::vex::product::all_products;
+use spdx_rs::models::SPDX;
+
+fn load_csaf() -> Csaf {
+    serde_json::from_str(include_str!("data/csaf/product-coverage.json")).unwrap()
+}
+
+fn load_spdx() -> SPDX {
+    serde_json::from_str(include_str!("data/spdx/appliance.json")).unwrap()
+}
+
+#[test]
+fn correlate() {
+    let csaf = load_csaf();
+    let products = all_products(&csaf)
+        .into_iter()
+        .map(|product| VexProduct {
+            advisory: csaf.document.tracking.id.clone(),
+            product_id: product.product_id.0.clone(),
+            name: product.name.clone(),
+            helpers: Helpers::from_product(product),
+        })
+        .filter(|product| !product.helpers.is_empty());
+
+    let mut correlate = Correlate::new(products);
+    correlate
+        .process(&ProgressBar::hidden(), &load_spdx())
+        .unwrap();
+
+    let matched = correlate
+        .correlations
+        .iter()
+        .filter(|c| !c.matches.is_empty())
+        .map(|c| {
+            (
+                c.product.product_id.as_str(),
+                c.matches[0].spdx_id.as_str(),
+                c.matches[0].main,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        matched,
+        [
+            ("appliance-1.0", "SPDXRef-appliance", true),
+            ("appliance-1.0:firmware", "SPDXRef-firmware", false),
+            ("openssl-1:1.1.1k-7.el8", "SPDXRef-openssl", false),
+        ]
+    );
+
+    let appliance = correlate
+        .correlations
+        .iter()
+        .find(|c| c.product.product_id == "appliance-1.0")
+        .unwrap();
+    assert_eq!(
+        appliance.sboms().into_iter().collect::<Vec<_>>(),
+        ["appliance-1.0"]
+    );
+    assert_eq!(
+        appliance.matches[0].evidence.iter().collect::<Vec<_>>(),
+        [&Evidence::Cpe]
+    );
+    assert_eq!(
+        appliance.matches[0].namespace,
+        "https://example.com/sbom/appliance-1.0"
+    );
+}
+
+#[test]
+fn fixup() {
+    assert_eq!(
+        fixup_cpe("cpe:/a:redhat:openssl:*".to_string()),
+        "cpe:/a:redhat:openssl:"
+    );
+    assert_eq!(
+        fixup_cpe("cpe:2.3:a:redhat:openssl:*:*:*:*:*:*:*:*".to_string()),
+        "cpe:2.3:a:redhat:openssl:*:*:*:*:*:*:*:*"
+    );
+}
+
+#[test]
+fn cpes() {
+    let resolutions = resolve_cpes(
+        [
+            "cpe:/a:redhat:openssl:*".to_string(),
+            "cpe:/a:redhat:kernel".to_string(),
+            "not a cpe".to_string(),
+        ],
+        [
+            "cpe:/a:redhat:openssl:1.1.1k".to_string(),
+            "cpe:2.3:a:redhat:openssl:3.0.7:*:*:*:*:*:*:*".to_string(),
+            "cpe:/a:redhat:openssh:8.0".to_string(),
+        ],
+    );
+
+    assert_eq!(resolutions.len(), 2);
+    assert_eq!(resolutions[0].source, "cpe:/a:redhat:openssl:");
+    assert_eq!(
+        resolutions[0].targets,
+        [
+            "cpe:/a:redhat:openssl:1.1.1k",
+            "cpe:2.3:a:redhat:openssl:3.0.7:*:*:*:*:*:*:*"
+        ]
+    );
+    assert_eq!(resolutions[1].source, "cpe:/a:redhat:kernel");
+    assert!(resolutions[1].targets.is_empty());
+}
+
+#[test]
+fn purls() {
+    let resolutions = resolve_purls(
+        [
+            "pkg:rpm/redhat/openssl@1.1.1k-7.el8_6".to_string(),
+            "pkg:npm/@angular/core".to_string(),
+        ],
+        [
+            "pkg:rpm/redhat/openssl@1.1.1k-7.el8_6?arch=x86_64".to_string(),
+            "pkg:rpm/redhat/openssl@1.1.1k-8.el8_6?arch=x86_64".to_string(),
+            "pkg:npm/%40angular/core@16.0.0".to_string(),
+        ],
+    );
+
+    assert_eq!(resolutions.len(), 2);
+    assert_eq!(
+        resolutions[0].targets,
+        ["pkg:rpm/redhat/openssl@1.1.1k-7.el8_6?arch=x86_64"]
+    );
+    assert_eq!(resolutions[1].source, "pkg:npm/%40angular/core");
+    assert_eq!(resolutions[1].targets, ["pkg:npm/%40angular/core@16.0.0"]);
+}
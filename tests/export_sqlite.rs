@@ -0,0 +1,83 @@
+mod common;
+
+use common::{load_advisory, load_spdx};
+use indicatif::ProgressBar;
+use playing_with_sboms::tasks::{export_sqlite, vex, Task};
+use playing_with_sboms::utils::vex::VexTask;
+use rusqlite::Connection;
+
+fn count(connection: &Connection, table: &str) -> usize {
+    connection
+        .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+}
+
+fn counts(connection: &Connection, tables: &[&str]) -> Vec<usize> {
+    tables
+        .iter()
+        .map(|table| count(connection, table))
+        .collect()
+}
+
+#[test]
+fn sbom() {
+    let tables = [
+        "documents",
+        "packages",
+        "external_references",
+        "checksums",
+        "licenses",
+        "relationships",
+    ];
+    let mut export =
+        export_sqlite::ExportSbomSqlite::new(Connection::open_in_memory().unwrap()).unwrap();
+    let spdx = load_spdx();
+
+    export.process(&ProgressBar::hidden(), &spdx).unwrap();
+    let first = counts(&export.connection, &tables);
+    assert_eq!(first, vec![1, 3, 4, 1, 3, 3]);
+
+    // exporting the same document again replaces it
+    export.process(&ProgressBar::hidden(), &spdx).unwrap();
+    assert_eq!(counts(&export.connection, &tables), first);
+
+    let main = export
+        .connection
+        .query_row("SELECT name FROM packages WHERE main = 1", [], |row| {
+            row.get::<_, String>(0)
+        })
+        .unwrap();
+    assert_eq!(main, "appliance");
+}
+
+#[test]
+fn foreign_keys() {
+    let export =
+        export_sqlite::ExportSbomSqlite::new(Connection::open_in_memory().unwrap()).unwrap();
+
+    // a checksum of a package which doesn't exist
+    assert!(export
+        .connection
+        .execute(
+            "INSERT INTO checksums (package_id, algorithm, value) VALUES (42, 'SHA256', '00')",
+            [],
+        )
+        .is_err());
+}
+
+#[tokio::test]
+async fn vex() {
+    let tables = ["advisories", "vex_products", "vex_statuses"];
+    let export =
+        vex::export_sqlite::ExportCsafSqlite::new(Connection::open_in_memory().unwrap()).unwrap();
+
+    export.process(load_advisory()).await.unwrap();
+    let first = counts(&export.connection.lock().unwrap(), &tables);
+    assert_eq!(first, vec![1, 6, 2]);
+
+    // exporting the same revision again replaces it
+    export.process(load_advisory()).await.unwrap();
+    assert_eq!(counts(&export.connection.lock().unwrap(), &tables), first);
+}
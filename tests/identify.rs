@@ -0,0 +1,193 @@
+use csaf::definitions::FullProductName;
+use csaf::Csaf;
+use playing_with_sboms::utils::vex::identify::{
+    match_packages, Evidence, Helpers, ProductRef, SbomIndex,
+};
+use playing_with_sboms::utils::vex::product::ProductIndex;
+use serde_json::json;
+use spdx_rs::models::SPDX;
+
+fn load_csaf() -> Csaf {
+    serde_json::from_str(include_str!("data/csaf/product-coverage.json")).unwrap()
+}
+
+fn load_spdx() -> SPDX {
+    serde_json::from_str(include_str!("data/spdx/appliance.json")).unwrap()
+}
+
+fn parse_helpers(helper: serde_json::Value) -> Helpers {
+    let product: FullProductName = serde_json::from_value(json!({
+        "name": "product",
+        "product_id": "product",
+        "product_identification_helper": helper,
+    }))
+    .unwrap();
+    Helpers::from_product(&product)
+}
+
+/// the SPDX IDs and evidence of the matching packages
+fn matches(helpers: &Helpers, sbom: &SbomIndex) -> Vec<(String, Vec<Evidence>)> {
+    match_packages(helpers, sbom)
+        .into_iter()
+        .map(|m| {
+            (
+                m.package.package_spdx_identifier.clone(),
+                m.evidence.into_iter().collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn evidence() {
+    let spdx = load_spdx();
+    let sbom = SbomIndex::new(&spdx);
+
+    let helpers = parse_helpers(json!({
+        "hashes": [{
+            "filename": "firmware-8.bin",
+            "file_hashes": [{
+                "algorithm": "sha-256",
+                "value": "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+            }]
+        }],
+        "cpe": "cpe:/o:example:appliance_firmware:1.0",
+    }));
+    assert_eq!(
+        matches(&helpers, &sbom),
+        [(
+            "SPDXRef-firmware".to_string(),
+            vec![Evidence::Hash, Evidence::Cpe]
+        )]
+    );
+
+    let helpers = parse_helpers(json!({
+        "purl": "pkg:rpm/example/openssl@1.1.1k-7.el8?epoch=1",
+    }));
+    assert_eq!(
+        matches(&helpers, &sbom),
+        [("SPDXRef-openssl".to_string(), vec![Evidence::Purl])]
+    );
+
+    let helpers = parse_helpers(json!({
+        "sbom_urls": ["https://example.com/sbom/appliance-1.0"],
+        "serial_numbers": ["SN-0815"],
+    }));
+    assert_eq!(
+        matches(&helpers, &sbom),
+        [(
+            "SPDXRef-appliance".to_string(),
+            vec![Evidence::SbomUrl, Evidence::SerialNumber]
+        )]
+    );
+
+    let helpers = parse_helpers(json!({
+        "x_generic_uris": [{
+            "namespace": "https://example.com/downloads",
+            "uri": "https://example.com/downloads/firmware-8.bin"
+        }],
+    }));
+    assert_eq!(
+        matches(&helpers, &sbom),
+        [("SPDXRef-firmware".to_string(), vec![Evidence::GenericUri])]
+    );
+}
+
+#[test]
+fn best_match_first() {
+    let spdx = load_spdx();
+    let sbom = SbomIndex::new(&spdx);
+
+    let helpers = parse_helpers(json!({
+        "cpe": "cpe:/h:example:appliance",
+        "purl": "pkg:rpm/example/openssl",
+    }));
+    let matches = match_packages(&helpers, &sbom);
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].package.package_name, "openssl");
+    assert_eq!(matches[0].score, Evidence::Purl.score());
+    assert_eq!(matches[1].package.package_name, "appliance");
+    assert!(matches[1].main);
+}
+
+#[test]
+fn no_false_matches() {
+    let spdx = load_spdx();
+    let sbom = SbomIndex::new(&spdx);
+
+    // the firmware has version "8", and the appliance a serial number, but no SKU or model number
+    let helpers = parse_helpers(json!({
+        "skus": ["8"],
+        "model_numbers": ["SN-0815", "firmware"],
+        "serial_numbers": ["8"],
+    }));
+    assert!(!helpers.is_empty());
+    assert!(match_packages(&helpers, &sbom).is_empty());
+
+    // the SBOM URL only identifies the main package
+    let helpers = parse_helpers(json!({
+        "sbom_urls": ["https://example.com/sbom/other"],
+    }));
+    assert!(match_packages(&helpers, &sbom).is_empty());
+
+    assert!(match_packages(&Helpers::default(), &sbom).is_empty());
+}
+
+#[test]
+fn match_sbom() {
+    let csaf = load_csaf();
+    let index = ProductIndex::new(&csaf);
+    let spdx = load_spdx();
+    let sbom = SbomIndex::new(&spdx);
+
+    // the firmware on the appliance, which is the main package
+    let product = ProductRef::resolve(&index, "appliance-1.0:firmware");
+    let matches = product.match_sbom(&sbom);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].package.package_name, "firmware");
+
+    // the platform is not the main package of the SBOM
+    let product = ProductRef::resolve(&index, "AppStream-8:openssl-1:1.1.1k-7.el8");
+    assert!(product.match_sbom(&sbom).is_empty());
+
+    // without a platform, the component itself
+    let product = ProductRef::resolve(&index, "openssl-1:1.1.1k-7.el8");
+    let matches = product.match_sbom(&sbom);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].package.package_name, "openssl");
+}
+
+#[test]
+fn several_platforms() {
+    // the firmware is installed on the appliance, and on a second product
+    let mut csaf: serde_json::Value =
+        serde_json::from_str(include_str!("data/csaf/product-coverage.json")).unwrap();
+    csaf["product_tree"]["relationships"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({
+            "category": "installed_on",
+            "full_product_name": {
+                "name": "Example Appliance firmware on Example Linux AppStream 8",
+                "product_id": "appliance-1.0:firmware",
+                "product_identification_helper": {
+                    "cpe": "cpe:/o:example:appliance_firmware:1.0"
+                }
+            },
+            "product_reference": "firmware",
+            "relates_to_product_reference": "AppStream-8"
+        }));
+    let csaf: Csaf = serde_json::from_value(csaf).unwrap();
+    let index = ProductIndex::new(&csaf);
+
+    let product = ProductRef::resolve(&index, "appliance-1.0:firmware");
+    assert_eq!(product.platforms.len(), 2);
+    assert_eq!(product.platforms[0].product.product_id, "appliance-1.0");
+    assert_eq!(product.platforms[1].product.product_id, "AppStream-8");
+
+    // the SBOM of the appliance matches one of the platforms
+    let spdx = load_spdx();
+    let matches = product.match_sbom(&SbomIndex::new(&spdx));
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].package.package_name, "firmware");
+}
@@ -0,0 +1,113 @@
+use csaf::Csaf;
+use playing_with_sboms::utils::vex::identify::ProductRef;
+use playing_with_sboms::utils::vex::product::{
+    collect_cpe, collect_purl, find_product_relations, group_products, has_product, trace_product,
+    ProductIndex,
+};
+
+fn load() -> Csaf {
+    serde_json::from_str(include_str!("data/csaf/product-coverage.json")).unwrap()
+}
+
+#[test]
+fn collect_from_all_definitions() {
+    let csaf = load();
+
+    assert_eq!(
+        collect_cpe(&csaf).into_iter().collect::<Vec<_>>(),
+        vec![
+            "cpe:/a:example:linux:8::appstream",
+            "cpe:/h:example:appliance:1.0",
+            "cpe:/o:example:appliance_firmware:1.0",
+        ]
+    );
+    assert_eq!(collect_purl(&csaf).len(), 1);
+}
+
+#[test]
+fn index() {
+    let csaf = load();
+    let index = ProductIndex::new(&csaf);
+
+    // branches
+    assert!(index.has_product("AppStream-8"));
+    assert_eq!(index.trace("openssl-1:1.1.1k-7.el8").len(), 3);
+    // full product names
+    assert!(!index.has_product("appliance-1.0"));
+    assert!(index.product("appliance-1.0").is_some());
+    assert!(index.trace("appliance-1.0").is_empty());
+    // relationships
+    assert!(!index.has_product("AppStream-8:openssl-1:1.1.1k-7.el8"));
+    assert!(index
+        .product("AppStream-8:openssl-1:1.1.1k-7.el8")
+        .is_some());
+    assert_eq!(index.relations("appliance-1.0:firmware").len(), 1);
+    // groups
+    assert_eq!(
+        index.group("all-openssl"),
+        [
+            "AppStream-8:openssl-1:1.1.1k-7.el8",
+            "appliance-1.0:firmware"
+        ]
+    );
+    assert!(index.group("unknown").is_empty());
+}
+
+#[test]
+fn free_functions() {
+    let csaf = load();
+    let index = ProductIndex::new(&csaf);
+
+    assert_eq!(
+        trace_product(&csaf, "openssl-1:1.1.1k-7.el8"),
+        index.trace("openssl-1:1.1.1k-7.el8")
+    );
+    assert!(has_product(&csaf, "AppStream-8"));
+    assert!(!has_product(&csaf, "appliance-1.0"));
+    assert!(!has_product(&csaf, "unknown"));
+    assert_eq!(
+        find_product_relations(&csaf, "appliance-1.0:firmware").count(),
+        1
+    );
+    assert_eq!(
+        group_products(&csaf, "all-openssl").collect::<Vec<_>>(),
+        index.group("all-openssl")
+    );
+}
+
+#[test]
+fn resolve() {
+    let csaf = load();
+    let index = ProductIndex::new(&csaf);
+
+    let product = ProductRef::resolve(&index, "AppStream-8:openssl-1:1.1.1k-7.el8");
+    assert_eq!(product.component.name, "openssl");
+    assert_eq!(product.component.version.as_deref(), Some("1:1.1.1k-7.el8"));
+    assert_eq!(product.platforms.len(), 1);
+    assert_eq!(product.platforms[0].category, "default_component_of");
+    assert_eq!(
+        product.platforms[0].product.cpe.as_deref(),
+        Some("cpe:/a:example:linux:8::appstream")
+    );
+    assert_eq!(
+        product.to_string(),
+        "openssl 1:1.1.1k-7.el8 (pkg:rpm/example/openssl@1.1.1k-7.el8?epoch=1) \
+         on Example Linux AppStream (cpe:/a:example:linux:8::appstream)"
+    );
+
+    let product = ProductRef::resolve(&index, "appliance-1.0");
+    assert_eq!(product.component.name, "Example Appliance 1.0");
+    assert!(product.platforms.is_empty());
+
+    // the component has no helpers, but the relationship's product has
+    let product = ProductRef::resolve(&index, "appliance-1.0:firmware");
+    assert_eq!(product.component.name, "Example Appliance firmware");
+    assert!(product.component.cpe.is_none());
+    assert_eq!(product.component.helpers.cpes.len(), 1);
+    assert!(!product.platforms[0].product.helpers.is_empty());
+
+    // not defined at all
+    let product = ProductRef::resolve(&index, "unknown");
+    assert_eq!(product.component.name, "unknown");
+    assert!(product.component.helpers.is_empty());
+}
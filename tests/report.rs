@@ -0,0 +1,120 @@
+mod common;
+
+use common::{advisory, load_csaf, load_spdx};
+use indicatif::ProgressBar;
+use playing_with_sboms::tasks::Task;
+use playing_with_sboms::utils::vex::report::{CollectStatements, SbomReport};
+use playing_with_sboms::utils::vex::VexTask;
+use serde_json::json;
+use std::collections::BTreeSet;
+
+/// the test advisory, with the firmware fixed in the next appliance, and the appliance itself
+/// not affected
+async fn statements() -> CollectStatements {
+    let mut csaf = load_csaf();
+    csaf["product_tree"]["full_product_names"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({"name": "Example Appliance 2.0", "product_id": "appliance-2.0"}));
+    csaf["product_tree"]["relationships"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({
+            "category": "installed_on",
+            "full_product_name": {
+                "name": "Example Appliance firmware on Example Appliance 2.0",
+                "product_id": "appliance-2.0:firmware"
+            },
+            "product_reference": "firmware",
+            "relates_to_product_reference": "appliance-2.0"
+        }));
+
+    let vulnerability = &mut csaf["vulnerabilities"][0];
+    vulnerability["product_status"]["fixed"] = json!(["appliance-2.0:firmware"]);
+    vulnerability["product_status"]["known_not_affected"] = json!(["appliance-1.0"]);
+    vulnerability["flags"] = json!([{
+        "label": "vulnerable_code_not_in_execute_path",
+        "product_ids": ["appliance-1.0"]
+    }]);
+
+    let task = CollectStatements::default();
+    task.process(advisory(csaf)).await.unwrap();
+    task
+}
+
+#[tokio::test]
+async fn report() {
+    let statements = statements().await.statements.into_inner();
+    let mut report = SbomReport::new("appliance-1.0", &statements);
+    report
+        .process(&ProgressBar::hidden(), &load_spdx())
+        .unwrap();
+    assert!(report.found);
+
+    let mut findings = report.findings;
+    findings.sort_by_key(|f| &f.statement.product.product_id);
+    let products = findings
+        .iter()
+        .map(|f| {
+            (
+                f.statement.product.product_id.as_str(),
+                f.statement.status.to_string(),
+                f.packages.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // openssl on AppStream-8, and the firmware of the next appliance, are not part of the SBOM
+    assert_eq!(
+        products,
+        vec![
+            (
+                "appliance-1.0",
+                "not_affected".to_string(),
+                BTreeSet::from([("appliance".to_string(), true)])
+            ),
+            (
+                "appliance-1.0:firmware",
+                "affected".to_string(),
+                BTreeSet::from([("firmware".to_string(), false)])
+            ),
+        ]
+    );
+
+    let not_affected = findings[0].statement;
+    assert_eq!(
+        not_affected.flags,
+        BTreeSet::from(["vulnerable_code_not_in_execute_path".to_string()])
+    );
+    assert!(not_affected.remediations.is_empty());
+    assert!(not_affected.fixed.is_empty());
+
+    let affected = findings[1].statement;
+    assert!(affected.flags.is_empty());
+    assert_eq!(
+        affected
+            .remediations
+            .iter()
+            .map(|r| (r.category.as_str(), r.url.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![(
+            "vendor_fix",
+            Some("https://example.com/errata/EXAMPLE-2023-0001")
+        )]
+    );
+    // the fixed firmware, but not openssl on AppStream-8, which is neither the same component nor
+    // on the same platform
+    assert_eq!(affected.fixed, vec!["appliance-2.0:firmware"]);
+}
+
+#[tokio::test]
+async fn other_sbom() {
+    let statements = statements().await.statements.into_inner();
+    let mut report = SbomReport::new("other", &statements);
+    report
+        .process(&ProgressBar::hidden(), &load_spdx())
+        .unwrap();
+
+    assert!(!report.found);
+    assert!(report.findings.is_empty());
+}
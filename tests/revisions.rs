@@ -0,0 +1,80 @@
+use csaf::Csaf;
+use playing_with_sboms::tasks::vex::revisions::{status_changes, Revision};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+fn load() -> Value {
+    serde_json::from_str(include_str!("data/csaf/product-coverage.json")).unwrap()
+}
+
+fn snapshot(value: Value) -> BTreeMap<String, Revision> {
+    let csaf: Csaf = serde_json::from_value(value).unwrap();
+    BTreeMap::from([(csaf.document.tracking.id.clone(), Revision::new(&csaf))])
+}
+
+#[test]
+fn unchanged() {
+    assert_eq!(status_changes(&snapshot(load()), &snapshot(load())), vec![]);
+}
+
+#[test]
+fn fixed() {
+    let mut value = load();
+    value["document"]["tracking"]["version"] = json!("2");
+    value["vulnerabilities"][0]["product_status"] = json!({
+        "fixed": ["AppStream-8:openssl-1:1.1.1k-7.el8"],
+        "known_affected": ["appliance-1.0:firmware"],
+        "under_investigation": ["AppStream-8"],
+    });
+
+    let changes = status_changes(&snapshot(load()), &snapshot(value))
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        changes,
+        vec![
+            "EXAMPLE-2023-0001 CVE-2023-0286 AppStream-8: none -> under_investigation",
+            "EXAMPLE-2023-0001 CVE-2023-0286 AppStream-8:openssl-1:1.1.1k-7.el8: affected -> fixed",
+        ]
+    );
+}
+
+#[test]
+fn other_advisory() {
+    let mut value = load();
+    value["document"]["tracking"]["id"] = json!("EXAMPLE-2023-0002");
+    value["vulnerabilities"][0]["product_status"] = json!({
+        "fixed": ["AppStream-8:openssl-1:1.1.1k-7.el8"],
+    });
+
+    assert_eq!(status_changes(&snapshot(load()), &snapshot(value)), vec![]);
+}
+
+#[test]
+fn latest() {
+    let revision = |version: &str, date: &str| {
+        let mut value = load();
+        value["document"]["tracking"]["version"] = json!(version);
+        value["document"]["tracking"]["current_release_date"] = json!(date);
+        let csaf: Csaf = serde_json::from_value(value).unwrap();
+        Revision::new(&csaf)
+    };
+
+    let first = revision("9", "2023-02-08T00:00:00Z");
+    let second = revision("10", "2023-02-08T00:00:00Z");
+    let third = revision("2", "2023-03-01T00:00:00Z");
+
+    // released at the same time, the version decides
+    assert!(second.is_later(&first));
+    assert!(!first.is_later(&second));
+    // otherwise the release date
+    assert!(third.is_later(&second));
+    assert!(!second.is_later(&third));
+    // the same revision isn't later than itself
+    assert!(!first.is_later(&first));
+
+    assert!(revision("1.10.0", "2023-02-08T00:00:00Z")
+        .is_later(&revision("1.9.0", "2023-02-08T00:00:00Z")));
+}
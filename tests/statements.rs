@@ -0,0 +1,82 @@
+use playing_with_sboms::utils::vex::statement::{Format, ProductIdentifier, VexDocument};
+use playing_with_sboms::utils::vex::status::Status;
+
+const OPENSSL: &str = "pkg:rpm/example/openssl@1.1.1k-7.el8?epoch=1";
+
+#[test]
+fn csaf() {
+    let document = VexDocument::parse(include_bytes!("data/csaf/product-coverage.json")).unwrap();
+
+    assert_eq!(document.format, Format::Csaf);
+    assert_eq!(document.id, "EXAMPLE-2023-0001");
+    assert_eq!(document.statements.len(), 2);
+
+    let statement = &document.statements[0];
+    assert_eq!(statement.vulnerability, "CVE-2023-0286");
+    assert_eq!(statement.status, Status::Affected);
+    // the component of the relationship
+    assert_eq!(
+        statement.product,
+        ProductIdentifier::Purl(OPENSSL.to_string())
+    );
+    assert!(statement.timestamp.is_some());
+
+    // no purl or CPE for the component
+    assert_eq!(
+        document.statements[1].product,
+        ProductIdentifier::Id("appliance-1.0:firmware".to_string())
+    );
+}
+
+#[test]
+fn openvex() {
+    let document = VexDocument::parse(include_bytes!("data/openvex/example.json")).unwrap();
+
+    assert_eq!(document.format, Format::OpenVex);
+    assert_eq!(document.statements.len(), 2);
+
+    let statement = &document.statements[0];
+    assert_eq!(
+        statement.product,
+        ProductIdentifier::Purl(OPENSSL.to_string())
+    );
+    assert_eq!(statement.status, Status::NotAffected);
+    assert_eq!(
+        statement.justification.as_deref(),
+        Some("vulnerable_code_not_in_execute_path")
+    );
+    assert_eq!(statement.timestamp.as_deref(), Some("2023-02-09T00:00:00Z"));
+
+    let statement = &document.statements[1];
+    assert_eq!(statement.vulnerability, "CVE-2023-0215");
+    assert_eq!(statement.status, Status::UnderInvestigation);
+    assert_eq!(statement.timestamp.as_deref(), Some("2023-02-08T00:00:00Z"));
+}
+
+#[test]
+fn cyclonedx() {
+    let document = VexDocument::parse(include_bytes!("data/cyclonedx/vex.json")).unwrap();
+
+    assert_eq!(document.format, Format::CycloneDx);
+    assert_eq!(document.statements.len(), 2);
+
+    let statement = &document.statements[0];
+    assert_eq!(
+        statement.product,
+        ProductIdentifier::Purl(OPENSSL.to_string())
+    );
+    assert_eq!(statement.status, Status::NotAffected);
+    assert_eq!(
+        statement.justification.as_deref(),
+        Some("code_not_reachable")
+    );
+
+    let statement = &document.statements[1];
+    assert_eq!(statement.status, Status::Fixed);
+    assert!(matches!(statement.product, ProductIdentifier::Id(_)));
+}
+
+#[test]
+fn unknown() {
+    assert!(VexDocument::parse(br#"{"foo": "bar"}"#).is_err());
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{load_advisory, load_csaf};
+use playing_with_sboms::tasks::vex::statistics::{StatementStatistics, Statistics};
+use playing_with_sboms::utils::vex::statement::{Format, VexDocument};
+use playing_with_sboms::utils::vex::status::Status;
+use playing_with_sboms::utils::vex::VexTask;
+use std::collections::BTreeMap;
+
+#[tokio::test]
+async fn statistics() {
+    let statistics = Statistics::default();
+    statistics.process(load_advisory()).await.unwrap();
+    statistics.process(load_advisory()).await.unwrap();
+
+    let counts = statistics.counts.lock().await;
+    assert_eq!(counts.total, 2);
+    assert_eq!(counts.years, BTreeMap::from([("2023".to_string(), 2)]));
+    assert_eq!(
+        counts.publishers,
+        BTreeMap::from([("Example".to_string(), 2)])
+    );
+    assert_eq!(counts.tlp, BTreeMap::from([("none".to_string(), 2)]));
+    assert_eq!(counts.cves, BTreeMap::from([(1, 2)]));
+    assert_eq!(counts.statuses, BTreeMap::from([(Status::Affected, 4)]));
+    assert!(counts.justifications.is_empty());
+    // three products, and three relationships
+    assert_eq!(counts.products, BTreeMap::from([(6, 2)]));
+    assert_eq!(counts.revisions, BTreeMap::from([(1, 2)]));
+}
+
+#[tokio::test]
+async fn statements() {
+    let statistics = StatementStatistics::default();
+    let csaf = serde_json::from_value(load_csaf()).unwrap();
+    statistics
+        .process_statements(&VexDocument::from_csaf(&csaf))
+        .await
+        .unwrap();
+
+    let counts = statistics.counts.lock().await;
+    assert_eq!(counts.formats, BTreeMap::from([(Format::Csaf, 1)]));
+    assert_eq!(
+        counts.statements,
+        BTreeMap::from([((Format::Csaf, Status::Affected), 2)])
+    );
+    assert!(counts.justifications.is_empty());
+}
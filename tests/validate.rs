@@ -0,0 +1,128 @@
+use csaf::Csaf;
+use playing_with_sboms::utils::vex::validate::{validate, Violation};
+use serde_json::{json, Value};
+
+fn load() -> Value {
+    serde_json::from_str(include_str!("data/csaf/product-coverage.json")).unwrap()
+}
+
+fn tests(value: Value) -> Vec<&'static str> {
+    let csaf: Csaf = serde_json::from_value(value).unwrap();
+    validate(&csaf).iter().map(Violation::test).collect()
+}
+
+#[test]
+fn conforming() {
+    assert_eq!(tests(load()), Vec::<&str>::new());
+}
+
+#[test]
+fn product_definitions() {
+    let mut value = load();
+    let tree = &mut value["product_tree"];
+    tree["full_product_names"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({"name": "again", "product_id": "AppStream-8"}));
+    tree["product_groups"][0]["product_ids"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!("unknown"));
+    tree["relationships"][0]["product_reference"] = json!("AppStream-8:openssl-1:1.1.1k-7.el8");
+
+    assert_eq!(tests(value), vec!["6.1.3", "6.1.2", "6.1.1"]);
+}
+
+#[test]
+fn vulnerabilities() {
+    let mut value = load();
+    let vulnerability = &mut value["vulnerabilities"][0];
+    vulnerability["product_status"]["fixed"] = json!(["appliance-1.0:firmware"]);
+    vulnerability["remediations"][0]["group_ids"] = json!(["unknown"]);
+    vulnerability["scores"] = json!([{
+        "products": ["appliance-1.0:firmware"],
+        "cvss_v3": {
+            "version": "3.1",
+            "vectorString": "CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:H/I:N/A:H",
+            "attackVector": "NETWORK",
+            "attackComplexity": "LOW",
+            "baseScore": 7.4,
+            "baseSeverity": "HIGH"
+        }
+    }]);
+    let copy = vulnerability.clone();
+    value["vulnerabilities"].as_array_mut().unwrap().push(copy);
+
+    let mut result = tests(value);
+    result.sort();
+    result.dedup();
+    assert_eq!(result, vec!["6.1.10", "6.1.23", "6.1.4", "6.1.6"]);
+}
+
+#[test]
+fn document_version() {
+    let mut value = load();
+    value["document"]["tracking"]["version"] = json!("2");
+
+    assert_eq!(tests(value), vec!["6.1.16"]);
+}
+
+#[test]
+fn cvss_scores() {
+    let mut value = load();
+    value["vulnerabilities"][0]["scores"] = json!([{
+        "products": ["appliance-1.0:firmware"],
+        "cvss_v2": {
+            "version": "2.0",
+            "vectorString": "AV:N/AC:L/Au:N/C:P/I:P/A:P",
+            "accessVector": "NETWORK",
+            "accessComplexity": "MEDIUM",
+            "authentication": "NONE",
+            "baseScore": 7.5
+        },
+        "cvss_v3": {
+            "version": "3.1",
+            "vectorString": "CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:H/I:N/A:H",
+            "attackVector": "NETWORK",
+            "attackComplexity": "HIGH",
+            "baseScore": 7.4,
+            "baseSeverity": "HIGH"
+        }
+    }]);
+
+    let csaf: Csaf = serde_json::from_value(value).unwrap();
+    let violations = validate(&csaf);
+    assert_eq!(
+        violations,
+        vec![Violation::InconsistentCvss {
+            vulnerability: "CVE-2023-0286".to_string(),
+            property: "accessComplexity",
+            vector: "AV:N/AC:L/Au:N/C:P/I:P/A:P".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn indirect_circular_definition() {
+    let mut value = load();
+    let relationships = value["product_tree"]["relationships"]
+        .as_array_mut()
+        .unwrap();
+    for (id, component) in [("loop-a", "loop-b"), ("loop-b", "loop-a")] {
+        relationships.push(json!({
+            "category": "default_component_of",
+            "full_product_name": {"name": id, "product_id": id},
+            "product_reference": component,
+            "relates_to_product_reference": "AppStream-8"
+        }));
+    }
+
+    let csaf: Csaf = serde_json::from_value(value).unwrap();
+    assert_eq!(
+        validate(&csaf),
+        vec![
+            Violation::CircularProductDefinition("loop-a".to_string()),
+            Violation::CircularProductDefinition("loop-b".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,106 @@
+use csaf_walker::discover::DiscoveredAdvisory;
+use csaf_walker::retrieve::{RetrievalMetadata, RetrievedAdvisory, RetrievedDigest};
+use playing_with_sboms::utils::vex::verify::{VerificationError, Verifier};
+use sha2::{Digest, Sha256, Sha512};
+use std::path::Path;
+
+const DOCUMENT: &str = "tests/data/csaf/product-coverage.json";
+const KEYRING: &str = "tests/data/openpgp/keyring.asc";
+const SIGNATURE: &str = "tests/data/openpgp/product-coverage.json.asc";
+
+/// retrieve the document, without any digests or signature
+fn retrieve() -> RetrievedAdvisory {
+    RetrievedAdvisory {
+        discovered: DiscoveredAdvisory {
+            url: "https://example.com/advisories/product-coverage.json"
+                .parse()
+                .unwrap(),
+        },
+        data: std::fs::read(DOCUMENT).unwrap().into(),
+        signature: None,
+        sha256: None,
+        sha512: None,
+        metadata: RetrievalMetadata {
+            last_modification: None,
+            etag: None,
+        },
+    }
+}
+
+/// the digests of the walker, expecting `expected`
+fn with_digests(mut retrieved: RetrievedAdvisory, expected: &[u8]) -> RetrievedAdvisory {
+    retrieved.sha256 = Some(RetrievedDigest {
+        expected: format!("{:x}  product-coverage.json\n", Sha256::digest(expected)),
+        actual: Sha256::digest(&retrieved.data),
+    });
+    retrieved.sha512 = Some(RetrievedDigest {
+        expected: format!("{:x}  product-coverage.json\n", Sha512::digest(expected)),
+        actual: Sha512::digest(&retrieved.data),
+    });
+    retrieved
+}
+
+fn kind(result: Result<(), VerificationError>) -> Option<&'static str> {
+    result.err().map(|err| err.kind())
+}
+
+#[test]
+fn disabled() {
+    let verifier = Verifier::new(false, None).unwrap();
+    assert!(!verifier.is_enabled());
+    assert_eq!(kind(verifier.verify(&retrieve())), None);
+}
+
+#[test]
+fn digests() {
+    let verifier = Verifier::new(true, None).unwrap();
+    let retrieved = retrieve();
+    let data = retrieved.data.clone();
+
+    assert_eq!(kind(verifier.verify(&with_digests(retrieved, &data))), None);
+}
+
+#[test]
+fn digest_mismatch() {
+    let verifier = Verifier::new(true, None).unwrap();
+
+    assert_eq!(
+        kind(verifier.verify(&with_digests(retrieve(), b"something else"))),
+        Some("digest-mismatch")
+    );
+}
+
+#[test]
+fn missing_digest() {
+    let verifier = Verifier::new(true, None).unwrap();
+
+    assert_eq!(kind(verifier.verify(&retrieve())), Some("missing-digest"));
+}
+
+#[test]
+fn signature() {
+    let verifier = Verifier::new(false, Some(Path::new(KEYRING))).unwrap();
+    let mut retrieved = retrieve();
+    retrieved.signature = Some(std::fs::read_to_string(SIGNATURE).unwrap());
+
+    assert_eq!(kind(verifier.verify(&retrieved)), None);
+}
+
+#[test]
+fn missing_signature() {
+    let verifier = Verifier::new(false, Some(Path::new(KEYRING))).unwrap();
+
+    assert_eq!(
+        kind(verifier.verify(&retrieve())),
+        Some("missing-signature")
+    );
+}
+
+#[test]
+fn bad_signature() {
+    let verifier = Verifier::new(false, Some(Path::new(KEYRING))).unwrap();
+    let mut retrieved = retrieve();
+    retrieved.signature = Some("not a signature".to_string());
+
+    assert_eq!(kind(verifier.verify(&retrieved)), Some("invalid-signature"));
+}